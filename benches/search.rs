@@ -0,0 +1,81 @@
+// `cargo bench`: the attempts and time the search takes on a few fixed
+// boards, with the heuristics that change its work turned on and off
+//
+// plain `Instant` timings over a few runs, printed as a table
+use std::time::{Duration, Instant};
+
+use lyne::{format_duration, Board, Color, DirectionOrder, SolveOptions};
+
+const RUNS: u32 = 5;
+
+// a 5x5 board with eight white nodes of 2 and 3
+const WHITES: &str = "bb2R.\nb222R\nB32Br\nG222.\nGgggg";
+
+// a single red path between neighboring endpoints, which it passes by many
+// times before covering the other nodes
+const ADJACENT_ENDS: &str = "RRrrr\nrrrrr\nrrrrr\nrrrrr";
+
+fn bench(name: &str, text: &str, options: SolveOptions) {
+    let mut total = Duration::ZERO;
+    let mut stats = None;
+    for _ in 0..RUNS {
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            threads: 1,
+            ..options.clone()
+        });
+        let start = Instant::now();
+        assert!(board.solve().is_some(), "{} has no solution", name);
+        total += start.elapsed();
+        stats = Some(board.stats().clone());
+    }
+    let stats = stats.unwrap();
+    println!(
+        "{:<24} {:>10} attempts {:>8} color checks {:>12}",
+        name,
+        stats.add_line_calls,
+        stats.color_checks,
+        format_duration(total / RUNS)
+    );
+}
+
+fn main() {
+    bench("whites, pruned", WHITES, SolveOptions::default());
+    bench(
+        "whites, not pruned",
+        WHITES,
+        SolveOptions {
+            prune: false,
+            ..SolveOptions::default()
+        },
+    );
+    bench(
+        "whites, blue first",
+        WHITES,
+        SolveOptions {
+            color_order: Some(vec![Color::Blue, Color::Green, Color::Red]),
+            ..SolveOptions::default()
+        },
+    );
+    bench(
+        "whites, greedy",
+        WHITES,
+        SolveOptions {
+            direction_order: DirectionOrder::Greedy,
+            ..SolveOptions::default()
+        },
+    );
+    bench(
+        "adjacent ends, pruned",
+        ADJACENT_ENDS,
+        SolveOptions::default(),
+    );
+    bench(
+        "adjacent ends",
+        ADJACENT_ENDS,
+        SolveOptions {
+            prune: false,
+            ..SolveOptions::default()
+        },
+    );
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::{Board, Color, ParseError, Symbol};
+
+// Characters standing for the nodes in the puzzles of an archive using other
+// letters, read from a `[symbols]` table of a TOML file:
+//
+// ```toml
+// [symbols]
+// red = "t"
+// red_end = "T"
+// empty = "-"
+// white = "o"
+// ```
+//
+// a color is named in lowercase, `red` or `a`, with `_end` for its
+// endpoints, `empty` is an empty cell and `white` the mark before the count
+// of a white node, as `◯` in `◯2`. Characters not listed keep their meaning
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Alphabet {
+    // each character listed, to the one the puzzle format reads
+    map: HashMap<char, char>,
+}
+
+impl Alphabet {
+    // read the `[symbols]` table of a TOML file, other tables are ignored
+    pub fn parse(text: &str) -> Result<Alphabet, ParseError> {
+        let mut map = HashMap::new();
+        let mut in_symbols = false;
+        for (i, line) in text.lines().enumerate() {
+            let invalid = || ParseError::InvalidAlphabet {
+                line: i + 1,
+                text: line.trim().to_string(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[') {
+                let (name, rest) = table.split_once(']').ok_or_else(invalid)?;
+                if !rest.trim().is_empty() && !rest.trim().starts_with('#') {
+                    return Err(invalid());
+                }
+                in_symbols = name.trim() == "symbols";
+                continue;
+            }
+            if !in_symbols {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let standard = standard_char(key.trim()).ok_or_else(invalid)?;
+            let c = toml_char(value.trim()).ok_or_else(invalid)?;
+            if c.is_whitespace() || map.insert(c, standard).is_some() {
+                return Err(invalid());
+            }
+        }
+        Ok(Alphabet { map })
+    }
+
+    // `text` with the characters listed replaced by the ones of the puzzle
+    // format, so errors keep pointing at the same columns
+    pub fn translate(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| self.map.get(&c).copied().unwrap_or(c))
+            .collect()
+    }
+}
+
+impl Board {
+    // parse a puzzle written with `alphabet`, as `Board::parse` otherwise
+    pub fn parse_with(text: &str, alphabet: &Alphabet) -> Result<Board, ParseError> {
+        Board::parse(&alphabet.translate(text))
+    }
+}
+
+// the character of the puzzle format for a key of the `[symbols]` table
+fn standard_char(key: &str) -> Option<char> {
+    match key {
+        "empty" => return Some('.'),
+        "white" => return Some('◯'),
+        _ => {}
+    }
+    let (name, end) = match key.strip_suffix("_end") {
+        Some(name) => (name, true),
+        None => (key, false),
+    };
+    let color = Color::iter().find(|c| c.to_string().to_lowercase() == name)?;
+    let symbol = if end {
+        Symbol::End(color)
+    } else {
+        Symbol::Node(color)
+    };
+    symbol.to_string().chars().next()
+}
+
+// a TOML string holding a single character, `"t"` or `'t'`, with a comment
+// after it or not
+fn toml_char(value: &str) -> Option<char> {
+    let quote = value.chars().next().filter(|&q| q == '"' || q == '\'')?;
+    let mut chars = value[1..].chars();
+    let c = match chars.next()? {
+        // a basic string escapes its backslashes and quotes, a literal one
+        // takes them as they are
+        '\\' if quote == '"' => match chars.next()? {
+            c @ ('\\' | '"') => c,
+            _ => return None,
+        },
+        c if c == quote => return None,
+        c => c,
+    };
+    if chars.next()? != quote {
+        return None;
+    }
+    let rest = chars.as_str().trim();
+    (rest.is_empty() || rest.starts_with('#')).then_some(c)
+}
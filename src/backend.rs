@@ -0,0 +1,69 @@
+use crate::{Algorithm, Board, Solution, VerifyError};
+
+// A search for the first solution of a board, so that other algorithms,
+// instrumented searches or stand-ins can replace `Board::solve`
+//
+// closures taking the board implement it too
+pub trait Solver {
+    fn solve(&self, board: &Board) -> Option<Solution>;
+}
+
+// the search of `Board::solve` with this algorithm, on a copy of the board
+// keeping its other options
+impl Solver for Algorithm {
+    fn solve(&self, board: &Board) -> Option<Solution> {
+        let mut board = board.clone();
+        board.options.algorithm = *self;
+        board.solve()
+    }
+}
+
+impl<F: Fn(&Board) -> Option<Solution>> Solver for F {
+    fn solve(&self, board: &Board) -> Option<Solution> {
+        self(board)
+    }
+}
+
+// How two solvers disagree on a board, each named by its place in the pair
+// given to `cross_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Disagreement {
+    // only this one found a solution
+    Solvability(usize),
+    // the solution this one found does not solve the board
+    Invalid(usize, VerifyError),
+}
+
+impl std::fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Disagreement::Solvability(solver) => {
+                write!(f, "only solver {} finds a solution", solver + 1)
+            }
+            Disagreement::Invalid(solver, e) => {
+                write!(f, "the solution of solver {} is wrong: {}", solver + 1, e)
+            }
+        }
+    }
+}
+
+// solve `board` with both solvers, and check that they agree on whether it
+// has a solution and that the solutions they find solve it
+//
+// a search giving up on its timeout counts as finding no solution
+pub fn cross_check(board: &Board, solvers: [&dyn Solver; 2]) -> Result<(), Disagreement> {
+    let solutions = solvers.map(|solver| solver.solve(board));
+    for (i, solution) in solutions.iter().enumerate() {
+        if let Some(solution) = solution {
+            board
+                .clone()
+                .verify(solution.added())
+                .map_err(|e| Disagreement::Invalid(i, e))?;
+        }
+    }
+    match solutions {
+        [Some(_), None] => Err(Disagreement::Solvability(0)),
+        [None, Some(_)] => Err(Disagreement::Solvability(1)),
+        _ => Ok(()),
+    }
+}
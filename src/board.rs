@@ -0,0 +1,1227 @@
+use iter_tools::Itertools;
+use log::trace;
+use strum::IntoEnumIterator;
+
+use crate::solution::chain;
+use crate::solver::SolveOptions;
+use crate::{node_order, Color, ColorSet, Direction, DirectionInner, Point, Stats, Symbol};
+
+// Inconsistency found between `lines`, the per-cell counters and `result`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    // a `result` entry whose `lines` slot is empty or has another color
+    MissingLine(Point, Direction, Color),
+    // a `lines` slot that no `result` entry accounts for
+    UnrecordedLine(Point, DirectionInner, Color),
+    // the counter of a cell disagrees with the lines entering it
+    DegreeMismatch {
+        pos: Point,
+        counter: u8,
+        incoming: u8,
+    },
+    // two beveled edges cross between the 4 cells around `pos`
+    Crossing(Point),
+    // `degree` disagrees with the lines touching the node at `pos`
+    LineCountMismatch {
+        pos: Point,
+        counted: u8,
+        touching: u8,
+    },
+    // `uncovered` disagrees with the nodes of `color` without a line
+    UncoveredMismatch {
+        color: Color,
+        counted: usize,
+        actual: usize,
+    },
+    // `whites_left` disagrees with the white nodes not passed their number
+    // of times
+    WhitesLeftMismatch {
+        counted: usize,
+        actual: usize,
+    },
+    // `hash` disagrees with the lines on the board
+    HashMismatch {
+        counted: u64,
+        actual: u64,
+    },
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::MissingLine(pos, direction, color) => write!(
+                f,
+                "{} line {} from {:?} is recorded but not placed",
+                color, direction, pos
+            ),
+            StateError::UnrecordedLine(pos, direction, color) => write!(
+                f,
+                "{} line {:?} from {:?} is placed but not recorded",
+                color, direction, pos
+            ),
+            StateError::DegreeMismatch {
+                pos,
+                counter,
+                incoming,
+            } => write!(
+                f,
+                "node {:?} counts {} lines but {} lines enter it",
+                pos, counter, incoming
+            ),
+            StateError::Crossing(pos) => {
+                write!(f, "beveled edges cross right below {:?}", pos)
+            }
+            StateError::LineCountMismatch {
+                pos,
+                counted,
+                touching,
+            } => write!(
+                f,
+                "node {:?} counts {} lines touching it but {} lines do",
+                pos, counted, touching
+            ),
+            StateError::UncoveredMismatch {
+                color,
+                counted,
+                actual,
+            } => write!(
+                f,
+                "{} {} nodes are counted without a line but {} are",
+                counted, color, actual
+            ),
+            StateError::WhitesLeftMismatch { counted, actual } => write!(
+                f,
+                "{} white nodes are counted as not passed enough but {} are",
+                counted, actual
+            ),
+            StateError::HashMismatch { counted, actual } => write!(
+                f,
+                "the hash of the lines is {:#x} but they hash to {:#x}",
+                counted, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+// Why a line cannot be added to or removed from the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineError {
+    // one of its ends, or the node given, is outside the board
+    OutOfBounds,
+    // a white node at one of its ends does not let its color through
+    ColorNotAllowed,
+    // it crosses the other diagonal between the same 4 nodes
+    Crossing,
+    // the node it enters has no pass left
+    NodeFull,
+    // a node at one of its ends has another color
+    ColorMismatch,
+    // the same line is already on the board
+    AlreadyDrawn,
+    // it neither leaves an endpoint nor continues the path of its color
+    NotOnPath,
+    // the line to remove is not on the board
+    NotFound,
+    // the line to remove is not the last one drawn
+    NotLast,
+    // the line to remove was fixed by `fix_lines`
+    Fixed,
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            LineError::OutOfBounds => "it leaves the board",
+            LineError::ColorNotAllowed => "its color may not pass through a white node",
+            LineError::Crossing => "it crosses another line",
+            LineError::NodeFull => "the node it enters is already full",
+            LineError::ColorMismatch => "it connects a node of another color",
+            LineError::AlreadyDrawn => "it is already drawn",
+            LineError::NotOnPath => "it does not continue the path of its color",
+            LineError::NotFound => "it is not on the board",
+            LineError::NotLast => "it is not the last line drawn",
+            LineError::Fixed => "it was fixed before solving",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl std::error::Error for LineError {}
+
+// A line of `Board::import_lines` that cannot be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalMove {
+    pub index: usize,
+    pub line: (Point, Direction, Color),
+    pub reason: LineError,
+}
+
+impl std::fmt::Display for IllegalMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (pos, direction, color) = self.line;
+        write!(
+            f,
+            "segment {} ({} {} from {:?}) is illegal: {}",
+            self.index + 1,
+            color,
+            direction,
+            pos,
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub(crate) board: Vec<(Symbol, u8)>, // simluates a 2d array
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) allowed: Vec<ColorSet>, // colors allowed on each node, only restricted for white nodes
+    pub(crate) lines: Vec<[Option<Color>; 4]>, // store the currect state of conneced lines, index by start position of the line
+    pub(crate) result: Vec<(Point, Direction, Color)>,
+    pub(crate) colors: Vec<Color>, // colors on the board, in the order they are solved
+    pub(crate) uncovered: Vec<usize>, // nodes of each color without any line, by `Color::index`
+    pub(crate) whites_left: usize, // white nodes not passed their number of times
+    pub(crate) degree: Vec<u8>,    // number of lines touching each node, in both directions
+    pub(crate) options: SolveOptions,
+    pub(crate) stats: Stats,
+    pub(crate) frozen: usize, // number of lines at the start of `result` fixed by `fix_lines`
+    pub(crate) hash: u64,     // Zobrist hash of `lines`, see `line_hash`
+    pub(crate) undone: Vec<(Point, Direction, Color)>, // lines taken back by `undo`, the last one first to redo
+}
+
+impl Board {
+    // create a board without any line from its nodes in reading order
+    //
+    // panics if the number of nodes is not a multiple of `width`
+    pub fn new(width: usize, symbols: Vec<Symbol>) -> Self {
+        assert!(
+            width > 0 && symbols.len().is_multiple_of(width),
+            "{} nodes cannot fill rows of width {}",
+            symbols.len(),
+            width
+        );
+        let mut board = Board {
+            colors: Vec::new(),
+            width,
+            height: symbols.len() / width,
+            allowed: vec![ColorSet::ALL; symbols.len()],
+            lines: vec![[None; 4]; symbols.len()],
+            degree: vec![0; symbols.len()],
+            uncovered: Color::iter()
+                .map(|c| symbols.iter().filter(|&&s| s == Symbol::color(c)).count())
+                .collect(),
+            whites_left: symbols
+                .iter()
+                .filter(|s| matches!(s, Symbol::White(n) if *n > 0))
+                .count(),
+            board: symbols.into_iter().map(|s| (s, 0)).collect(),
+            result: Vec::new(),
+            options: SolveOptions::default(),
+            stats: Stats::default(),
+            frozen: 0,
+            hash: 0,
+            undone: Vec::new(),
+        };
+        board.colors = board.solve_order();
+        board
+    }
+
+    // restrict the colors allowed to pass through the node at `pos`
+    //
+    // the lines taken back by `undo` are forgotten, as they may no longer
+    // be allowed
+    //
+    // fail with `LineError::OutOfBounds` if `pos` is outside the board
+    pub fn set_allowed(&mut self, pos: Point, allowed: ColorSet) -> Result<(), LineError> {
+        if !self.in_bounds(pos) {
+            return Err(LineError::OutOfBounds);
+        }
+        let index = self.index(pos);
+        self.allowed[index] = allowed;
+        self.undone.clear();
+        Ok(())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // the node at `pos`, or None outside the board
+    pub fn symbol(&self, pos: Point) -> Option<Symbol> {
+        self.in_bounds(pos).then(|| self.board[self.index(pos)].0)
+    }
+
+    // the number of lines touching the node at `pos`, or None outside the
+    // board
+    pub fn lines_at(&self, pos: Point) -> Option<u8> {
+        self.in_bounds(pos).then(|| self.degree[self.index(pos)])
+    }
+
+    // the colors appearing on the board
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    // all nodes in reading order
+    pub fn symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.board.iter().map(|s| s.0)
+    }
+
+    // convert a point to a index
+    //
+    // a point outside the board would wrap around to another node, so it
+    // must be checked with `in_bounds` first
+    #[inline]
+    pub(crate) fn index(&self, (x, y): Point) -> usize {
+        debug_assert!(self.in_bounds((x, y)), "{:?} is outside the board", (x, y));
+        (y * self.width as i32 + x) as usize
+    }
+
+    // convert a index to a point
+    #[inline]
+    pub(crate) fn pos(&self, index: usize) -> Point {
+        ((index % self.width) as i32, (index / self.width) as i32)
+    }
+
+    #[inline]
+    pub(crate) fn in_bounds(&self, (x, y): Point) -> bool {
+        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
+    }
+
+    // check whether a connected line could be added to the board
+    pub fn can_add_line(&self, start_pos: Point, direction: Direction, color: Color) -> bool {
+        self.check_line(start_pos, direction, color).is_ok()
+    }
+
+    // tell why a connected line could not be added to the board
+    pub fn check_line(
+        &self,
+        start_pos: Point,
+        direction: Direction,
+        color: Color,
+    ) -> Result<(), LineError> {
+        let offset_pos = direction.apply_offset(start_pos);
+        if !self.in_bounds(start_pos) || !self.in_bounds(offset_pos) {
+            return Err(LineError::OutOfBounds);
+        }
+
+        let offset_index = self.index(offset_pos);
+        if !self.allowed[self.index(start_pos)].contains(color)
+            || !self.allowed[offset_index].contains(color)
+        {
+            return Err(LineError::ColorNotAllowed);
+        }
+
+        if let Some((conflict_point, direction_inner)) = direction.may_conflict(start_pos) {
+            if self.lines[self.index(conflict_point)][direction_inner as usize].is_some() {
+                return Err(LineError::Crossing);
+            }
+        }
+        let offset_point = self.board[offset_index];
+        if let Symbol::White(n) = offset_point.0 {
+            if offset_point.1 + 1 > n {
+                return Err(LineError::NodeFull);
+            }
+        } else if offset_point.0 == Symbol::color(color)
+            || offset_point.0 == Symbol::color_end(color)
+        {
+            if offset_point.1 > 0 {
+                return Err(LineError::NodeFull);
+            }
+        } else {
+            return Err(LineError::ColorMismatch);
+        }
+        let (store_pos, direction_inner) = direction.store(start_pos);
+        let index = self.index(store_pos);
+        let point = self.board[index];
+        if !(point.0 == Symbol::color(color)
+            || point.0 == Symbol::color_end(color)
+            || matches!(point.0, Symbol::White(_)))
+        {
+            return Err(LineError::ColorMismatch);
+        }
+        if self.lines[index][direction_inner as usize].is_some() {
+            return Err(LineError::AlreadyDrawn);
+        }
+        Ok(())
+    }
+
+    // add a connected line to the board if it is legal, forgetting the lines
+    // that `redo` could draw again
+    //
+    // a line that is not legal leaves the board unchanged, only counting
+    // the attempt in `stats`
+    pub fn add_line(
+        &mut self,
+        start_pos: Point,
+        direction: Direction,
+        color: Color,
+    ) -> Result<(), LineError> {
+        trace!("try add line ({:?}, {}, {})", start_pos, direction, color);
+        self.stats.add_line_calls += 1;
+        self.check_line(start_pos, direction, color)?;
+        let offset_index = self.index(direction.apply_offset(start_pos));
+        let (store_pos, direction_inner) = direction.store(start_pos);
+        let index = self.index(store_pos);
+        self.lines[index][direction_inner as usize] = Some(color);
+        self.hash ^= line_hash(index, direction_inner, color);
+        self.count_pass(offset_index, true);
+        self.attach(offset_index, color);
+        self.attach(self.index(start_pos), color);
+        self.result.push((start_pos, direction, color));
+        self.undone.clear();
+        self.stats.lines_placed += 1;
+        if self.result.len() > self.stats.max_depth {
+            self.stats.max_depth = self.result.len();
+            self.stats.deepest.clone_from(&self.result);
+        }
+        Ok(())
+    }
+
+    // remove the last line drawn, given from either end
+    //
+    // lines are taken back in the reverse order they were drawn, anything
+    // else leaves the board unchanged
+    pub fn remove_line(&mut self, start_pos: Point, direction: Direction) -> Result<(), LineError> {
+        trace!("remove line ({:?}, {:?})", start_pos, direction);
+        let offset_pos = direction.apply_offset(start_pos);
+        if !self.in_bounds(start_pos) || !self.in_bounds(offset_pos) {
+            return Err(LineError::OutOfBounds);
+        }
+        let (store_pos, direction_inner) = direction.store(start_pos);
+        let store_index = self.index(store_pos);
+        if self.lines[store_index][direction_inner as usize].is_none() {
+            return Err(LineError::NotFound);
+        }
+        let &(last_pos, last_direction, color) = self.result.last().ok_or(LineError::NotFound)?;
+        if last_direction.store(last_pos) != (store_pos, direction_inner) {
+            return Err(LineError::NotLast);
+        }
+        if self.result.len() <= self.frozen {
+            return Err(LineError::Fixed);
+        }
+
+        // the node the line entered when it was drawn holds its count
+        let offset_index = self.index(last_direction.apply_offset(last_pos));
+        self.count_pass(offset_index, false);
+        self.lines[store_index][direction_inner as usize] = None;
+        self.hash ^= line_hash(store_index, direction_inner, color);
+        self.detach(offset_index, color);
+        self.detach(self.index(last_pos), color);
+        self.result.pop();
+        self.stats.backtracks += 1;
+        Ok(())
+    }
+
+    // count a line entering the node at `index`, or forget it, keeping
+    // `whites_left` up to date
+    fn count_pass(&mut self, index: usize, entered: bool) {
+        let passed = |&(symbol, count): &(Symbol, u8)| symbol == Symbol::White(count);
+        let was_passed = passed(&self.board[index]);
+        if entered {
+            self.board[index].1 += 1;
+        } else {
+            self.board[index].1 -= 1;
+        }
+        match (was_passed, passed(&self.board[index])) {
+            (true, false) => self.whites_left += 1,
+            (false, true) => self.whites_left -= 1,
+            _ => {}
+        }
+    }
+
+    // count a line of `color` touching the node at `index`
+    fn attach(&mut self, index: usize, color: Color) {
+        if self.degree[index] == 0 && self.board[index].0 == Symbol::color(color) {
+            self.uncovered[color.index()] -= 1;
+        }
+        self.degree[index] += 1;
+    }
+
+    // forget a line of `color` touching the node at `index`
+    fn detach(&mut self, index: usize, color: Color) {
+        self.degree[index] -= 1;
+        if self.degree[index] == 0 && self.board[index].0 == Symbol::color(color) {
+            self.uncovered[color.index()] += 1;
+        }
+    }
+
+    // find the shortest path of `color` from `from` to `to` using only lines
+    // that could still be added to the current board
+    pub fn shortest_path_between(
+        &self,
+        from: Point,
+        to: Point,
+        color: Color,
+    ) -> Option<Vec<Point>> {
+        if !self.in_bounds(from) || !self.in_bounds(to) {
+            return None;
+        }
+        let mut previous = vec![None; self.board.len()];
+        let mut queue = std::collections::VecDeque::from([from]);
+        previous[self.index(from)] = Some(from);
+        while let Some(point) = queue.pop_front() {
+            if point == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while current != from {
+                    current = previous[self.index(current)].unwrap();
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if point != from && self.board[self.index(point)].0 == Symbol::color_end(color) {
+                // paths stop at the endpoints
+                continue;
+            }
+            for direction in Direction::iter() {
+                if !self.can_add_line(point, direction, color) {
+                    continue;
+                }
+                let next_point = direction.apply_offset(point);
+                let next_index = self.index(next_point);
+                if previous[next_index].is_none() {
+                    previous[next_index] = Some(point);
+                    queue.push_back(next_point);
+                }
+            }
+        }
+        None
+    }
+
+    // whether the lines of `color` form a single path between its endpoints
+    pub(crate) fn color_connected(&self, color: Color) -> bool {
+        let mut degree = vec![0u8; self.board.len()];
+        let mut neighbors = vec![Vec::new(); self.board.len()];
+        for &(pos, direction, line_color) in &self.result {
+            if line_color == color {
+                let (start, end) = (self.index(pos), self.index(direction.apply_offset(pos)));
+                degree[start] += 1;
+                degree[end] += 1;
+                neighbors[start].push(end);
+                neighbors[end].push(start);
+            }
+        }
+        let degree_ok = self.board.iter().zip(&degree).all(|(&(symbol, _), &d)| {
+            if symbol == Symbol::color_end(color) {
+                d == 1
+            } else if symbol == Symbol::color(color) {
+                d == 2
+            } else if matches!(symbol, Symbol::White(_)) {
+                d % 2 == 0
+            } else {
+                d == 0
+            }
+        });
+        if !degree_ok {
+            return false;
+        }
+
+        // with these degrees, the lines form one path if they are all connected
+        let Some(start) = self
+            .board
+            .iter()
+            .position(|&(symbol, _)| symbol == Symbol::color_end(color))
+        else {
+            return degree.iter().all(|&d| d == 0);
+        };
+        let mut visited = vec![false; self.board.len()];
+        let mut stack = vec![start];
+        visited[start] = true;
+        while let Some(index) = stack.pop() {
+            for &next in &neighbors[index] {
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        degree.iter().zip(&visited).all(|(&d, &v)| d == 0 || v)
+    }
+
+    // whether the current state is a complete and valid solution
+    pub fn is_solved(&self) -> bool {
+        self.check_solved().is_ok()
+    }
+
+    // chain the lines of `color` into the nodes visited from its first endpoint
+    pub fn ordered_path(&self, color: Color) -> Vec<Point> {
+        let lines = self
+            .result
+            .iter()
+            .filter(|s| s.2 == color)
+            .map(|&(pos, direction, _)| (pos, direction.apply_offset(pos)))
+            .collect::<Vec<_>>();
+        match (0..self.board.len())
+            .filter(|&i| self.board[i].0 == Symbol::color_end(color))
+            .map(|i| self.pos(i))
+            .min_by_key(|&pos| node_order(pos))
+        {
+            Some(start) => chain(start, lines),
+            None => Vec::new(),
+        }
+    }
+
+    // coalesce the straight runs of the path of `color` into
+    // `(start, direction, length)` segments
+    pub fn merge_collinear_segments(&self, color: Color) -> Vec<(Point, Direction, usize)> {
+        let mut runs: Vec<(Point, Direction, usize)> = Vec::new();
+        for (&from, &to) in self.ordered_path(color).iter().tuple_windows() {
+            let direction = Direction::from_offset((to.0 - from.0, to.1 - from.1)).unwrap();
+            match runs.last_mut() {
+                Some((_, last, length)) if *last == direction => *length += 1,
+                _ => runs.push((from, direction, 1)),
+            }
+        }
+        runs
+    }
+
+    // the placed lines, in the order they were added
+    pub fn export_lines(&self) -> Vec<(Point, Direction, Color)> {
+        self.result.clone()
+    }
+
+    // replay lines through `add_line`, leaving the board untouched if one of
+    // them is illegal
+    //
+    // a line leaving an endpoint that no line entered starts the path there,
+    // the same way `solve_color` does
+    pub fn import_lines(&mut self, lines: &[(Point, Direction, Color)]) -> Result<(), IllegalMove> {
+        let mut starts: Vec<usize> = Vec::new();
+        for (index, &(pos, direction, color)) in lines.iter().enumerate() {
+            let start = self
+                .in_bounds(pos)
+                .then(|| self.index(pos))
+                .filter(|&i| self.board[i].0 == Symbol::color_end(color) && self.board[i].1 == 0);
+            if let Some(i) = start {
+                self.board[i].1 += 1;
+            }
+            if let Err(reason) = self.add_line(pos, direction, color) {
+                if let Some(i) = start {
+                    self.board[i].1 -= 1;
+                }
+                for &(pos, direction, _) in lines[..index].iter().rev() {
+                    let removed = self.remove_line(pos, direction);
+                    debug_assert_eq!(removed, Ok(()));
+                }
+                for i in starts {
+                    self.board[i].1 -= 1;
+                }
+                return Err(IllegalMove {
+                    index,
+                    line: (pos, direction, color),
+                    reason,
+                });
+            }
+            starts.extend(start);
+        }
+        Ok(())
+    }
+
+    // place lines drawn beforehand that the search must keep, e.g. to get a
+    // hint on a puzzle partly solved by hand
+    //
+    // the lines of each color must be given in order, starting at one of its
+    // endpoints, so the search can continue the path where it stops
+    pub fn fix_lines(&mut self, lines: &[(Point, Direction, Color)]) -> Result<(), IllegalMove> {
+        let mut heads: Vec<(Color, Point)> = Vec::new();
+        for (index, &(pos, direction, color)) in lines.iter().enumerate() {
+            let on_path = match heads.iter_mut().find(|(c, _)| *c == color) {
+                Some((_, head)) => {
+                    // a head off the board is reported by `import_lines`
+                    let continues =
+                        *head == pos && self.symbol(pos) != Some(Symbol::color_end(color));
+                    *head = direction.apply_offset(pos);
+                    continues
+                }
+                None => {
+                    heads.push((color, direction.apply_offset(pos)));
+                    self.symbol(pos) == Some(Symbol::color_end(color))
+                }
+            };
+            if !on_path {
+                return Err(IllegalMove {
+                    index,
+                    line: (pos, direction, color),
+                    reason: LineError::NotOnPath,
+                });
+            }
+        }
+        self.import_lines(lines)?;
+        self.frozen = self.result.len();
+        Ok(())
+    }
+
+    // the lines placed by `fix_lines`, which the search never takes back
+    pub fn fixed_lines(&self) -> &[(Point, Direction, Color)] {
+        &self.result[..self.frozen]
+    }
+
+    // check that `lines`, the counters and `result` agree with each other,
+    // and that `degree`, `uncovered`, `whites_left` and `hash`, kept up to
+    // date by `add_line` and `remove_line`, match the lines on the board
+    //
+    // the solver only ever changes them together, so any error here means
+    // `add_line`/`remove_line` were misused
+    pub fn validate_state(&self) -> Result<(), StateError> {
+        let mut recorded = vec![[None; 4]; self.lines.len()];
+        let mut incoming = vec![0u8; self.board.len()];
+        for &(pos, direction, color) in &self.result {
+            let (store_pos, direction_inner) = direction.store(pos);
+            let index = self.index(store_pos);
+            if self
+                .lines
+                .get(index)
+                .map(|line| line[direction_inner as usize])
+                != Some(Some(color))
+                || recorded[index][direction_inner as usize].is_some()
+            {
+                return Err(StateError::MissingLine(pos, direction, color));
+            }
+            recorded[index][direction_inner as usize] = Some(color);
+            incoming[self.index(direction.apply_offset(pos))] += 1;
+        }
+
+        for (index, (line, recorded)) in self.lines.iter().zip(&recorded).enumerate() {
+            for (dir, (line_color, recorded_color)) in line.iter().zip(recorded).enumerate() {
+                if let (Some(color), None) = (line_color, recorded_color) {
+                    return Err(StateError::UnrecordedLine(
+                        self.pos(index),
+                        DirectionInner::from(dir as u8),
+                        *color,
+                    ));
+                }
+            }
+        }
+
+        for (index, (&(symbol, counter), &incoming)) in self.board.iter().zip(&incoming).enumerate()
+        {
+            // the starting endpoint of a color counts itself without a line
+            let is_start = counter == 1 && incoming == 0 && symbol.is_end();
+            if counter != incoming && !is_start {
+                return Err(StateError::DegreeMismatch {
+                    pos: self.pos(index),
+                    counter,
+                    incoming,
+                });
+            }
+        }
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let (x, y) = self.pos(index);
+            if line[DirectionInner::DownRight as usize].is_some()
+                && x + 1 < self.width as i32
+                && self.lines[self.index((x + 1, y))][DirectionInner::DownLeft as usize].is_some()
+            {
+                return Err(StateError::Crossing((x, y)));
+            }
+        }
+
+        let mut touching = vec![0u8; self.board.len()];
+        let mut hash = 0;
+        for (index, line) in self.lines.iter().enumerate() {
+            for (dir, color) in line.iter().enumerate() {
+                if let Some(color) = *color {
+                    let direction_inner = DirectionInner::from(dir as u8);
+                    let direction = Direction::from(direction_inner);
+                    touching[index] += 1;
+                    touching[self.index(direction.apply_offset(self.pos(index)))] += 1;
+                    hash ^= line_hash(index, direction_inner, color);
+                }
+            }
+        }
+        for (index, (&counted, &touching)) in self.degree.iter().zip(&touching).enumerate() {
+            if counted != touching {
+                return Err(StateError::LineCountMismatch {
+                    pos: self.pos(index),
+                    counted,
+                    touching,
+                });
+            }
+        }
+        for color in Color::iter() {
+            let actual = self
+                .board
+                .iter()
+                .zip(&touching)
+                .filter(|&(&(symbol, _), &touching)| {
+                    symbol == Symbol::color(color) && touching == 0
+                })
+                .count();
+            let counted = self.uncovered[color.index()];
+            if counted != actual {
+                return Err(StateError::UncoveredMismatch {
+                    color,
+                    counted,
+                    actual,
+                });
+            }
+        }
+        let actual = self
+            .board
+            .iter()
+            .filter(|&&(symbol, count)| matches!(symbol, Symbol::White(n) if n != count))
+            .count();
+        if self.whites_left != actual {
+            return Err(StateError::WhitesLeftMismatch {
+                counted: self.whites_left,
+                actual,
+            });
+        }
+        if self.hash != hash {
+            return Err(StateError::HashMismatch {
+                counted: self.hash,
+                actual: hash,
+            });
+        }
+        Ok(())
+    }
+}
+
+// a random number for each line that can be stored in `Board::lines`, so
+// the XOR of those on the board tells boards with other lines apart
+fn line_hash(index: usize, direction_inner: DirectionInner, color: Color) -> u64 {
+    // splitmix64 of the position of the line in `lines`
+    let mut z = ((index * 4 + direction_inner as usize) * 32 + color.index()) as u64;
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::VerifyError;
+
+    // `RrR` above `BbB`, with its red path drawn
+    fn red_drawn() -> Board {
+        let mut board = Board::parse("RrR\nBbB").unwrap();
+        board
+            .import_lines(&[
+                ((0, 0), Direction::Right, Color::Red),
+                ((1, 0), Direction::Right, Color::Red),
+            ])
+            .unwrap();
+        board
+    }
+
+    #[test]
+    fn validate_state_accepts_lines_drawn_by_add_line() {
+        assert_eq!(red_drawn().validate_state(), Ok(()));
+    }
+
+    #[test]
+    fn validate_state_rejects_a_line_missing_from_lines() {
+        let mut board = red_drawn();
+        board.lines[1][DirectionInner::Right as usize] = None;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::MissingLine(
+                (1, 0),
+                Direction::Right,
+                Color::Red
+            ))
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_a_line_missing_from_result() {
+        let mut board = red_drawn();
+        board.result.pop();
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::UnrecordedLine(
+                (1, 0),
+                DirectionInner::Right,
+                Color::Red
+            ))
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_a_counter_out_of_step() {
+        let mut board = red_drawn();
+        board.board[1].1 += 1;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::DegreeMismatch {
+                pos: (1, 0),
+                counter: 2,
+                incoming: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_crossing_lines() {
+        let mut board = Board::parse("Rb\nBr").unwrap();
+        board.lines[0][DirectionInner::DownRight as usize] = Some(Color::Red);
+        board.lines[1][DirectionInner::DownLeft as usize] = Some(Color::Blue);
+        board.result = vec![
+            ((0, 0), Direction::DownRight, Color::Red),
+            ((1, 0), Direction::DownLeft, Color::Blue),
+        ];
+        board.board[3].1 = 1;
+        board.board[2].1 = 1;
+        assert_eq!(board.validate_state(), Err(StateError::Crossing((0, 0))));
+    }
+
+    #[test]
+    fn validate_state_rejects_a_stale_degree() {
+        let mut board = red_drawn();
+        board.degree[2] = 0;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::LineCountMismatch {
+                pos: (2, 0),
+                counted: 0,
+                touching: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_a_stale_uncovered_count() {
+        let mut board = red_drawn();
+        board.uncovered[Color::Blue.index()] = 0;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::UncoveredMismatch {
+                color: Color::Blue,
+                counted: 0,
+                actual: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_a_stale_whites_left() {
+        let mut board = Board::parse("R1R").unwrap();
+        board
+            .add_line((0, 0), Direction::Right, Color::Red)
+            .unwrap();
+        board.whites_left = 1;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::WhitesLeftMismatch {
+                counted: 1,
+                actual: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_state_rejects_a_stale_hash() {
+        let mut board = red_drawn();
+        let actual = board.hash;
+        board.hash ^= 1;
+        assert_eq!(
+            board.validate_state(),
+            Err(StateError::HashMismatch {
+                counted: actual ^ 1,
+                actual,
+            })
+        );
+    }
+
+    #[test]
+    fn fix_lines_reports_segments_leaving_the_board() {
+        let mut board = Board::parse("RrR").unwrap();
+        let lines = [
+            ((0, 0), Direction::Left, Color::Red),
+            ((-1, 0), Direction::Right, Color::Red),
+        ];
+        let error = board.fix_lines(&lines).unwrap_err();
+        assert_eq!((error.index, error.reason), (0, LineError::OutOfBounds));
+        assert!(board.export_lines().is_empty());
+        assert!(board.fixed_lines().is_empty());
+    }
+
+    #[test]
+    fn a_white_node_only_lets_its_colors_through() {
+        let mut board = Board::parse("R 2rb R\nG . G").unwrap();
+        let green = ((0, 1), Direction::UpRight, Color::Green);
+        assert_eq!(
+            board.add_line(green.0, green.1, green.2),
+            Err(LineError::ColorNotAllowed)
+        );
+        assert!(board.export_lines().is_empty());
+        assert_eq!(board.add_line((0, 0), Direction::Right, Color::Red), Ok(()));
+        match Board::parse("R 2rb R\nG . G").unwrap().verify(&[green]) {
+            Err(VerifyError::IllegalSegment(e)) => assert_eq!(e.reason, LineError::ColorNotAllowed),
+            other => panic!("green line through 2rb verified as {:?}", other),
+        }
+
+        let mut open = Board::parse("R 2 R\nG . G").unwrap();
+        assert_eq!(open.add_line(green.0, green.1, green.2), Ok(()));
+    }
+
+    #[test]
+    fn shortest_path_between_takes_the_diagonals() {
+        let board = Board::parse("Rrrrr\nrrrrR").unwrap();
+        let path = board
+            .shortest_path_between((0, 0), (4, 1), Color::Red)
+            .unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!((path[0], path[4]), ((0, 0), (4, 1)));
+        for (from, to) in path.iter().tuple_windows() {
+            assert!(Direction::from_offset((to.0 - from.0, to.1 - from.1)).is_some());
+        }
+        assert_eq!(
+            board.shortest_path_between((0, 0), (4, 1), Color::Blue),
+            None
+        );
+    }
+
+    #[test]
+    fn points_outside_the_board_are_refused() {
+        let mut board = Board::parse("Rrrrr\nrrrrR").unwrap();
+        for pos in [(-1, 1), (5, 0), (0, 2), (0, -1)] {
+            assert_eq!(board.symbol(pos), None, "{:?}", pos);
+            assert_eq!(board.lines_at(pos), None, "{:?}", pos);
+            assert_eq!(
+                board.set_allowed(pos, ColorSet::empty()),
+                Err(LineError::OutOfBounds)
+            );
+            assert_eq!(board.shortest_path_between(pos, (4, 1), Color::Red), None);
+        }
+        assert_eq!(board.symbol((4, 0)), Some(Symbol::Node(Color::Red)));
+        assert_eq!(board.lines_at((4, 0)), Some(0));
+        assert_eq!(board.set_allowed((4, 0), ColorSet::empty()), Ok(()));
+    }
+
+    // the lines of the only path through `Rr` above `rR`
+    const ZIGZAG: [(Point, Direction, Color); 3] = [
+        ((0, 0), Direction::Right, Color::Red),
+        ((1, 0), Direction::DownLeft, Color::Red),
+        ((0, 1), Direction::Right, Color::Red),
+    ];
+
+    #[test]
+    fn is_solved_on_a_complete_board() {
+        let mut board = Board::parse("Rr\nrR").unwrap();
+        board.import_lines(&ZIGZAG).unwrap();
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn is_solved_on_a_partial_board() {
+        let mut board = Board::parse("Rr\nrR").unwrap();
+        assert!(!board.is_solved());
+        board.import_lines(&ZIGZAG[..2]).unwrap();
+        assert!(!board.is_solved());
+    }
+
+    #[test]
+    fn is_solved_on_an_overfilled_board() {
+        // drawn with `add_line` alone, nothing counts the first endpoint as
+        // the start of its path, so a line can come back to it
+        let mut board = Board::parse("Rr\nrR").unwrap();
+        for (pos, direction, color) in ZIGZAG {
+            assert_eq!(board.add_line(pos, direction, color), Ok(()));
+        }
+        assert!(board.is_solved());
+        assert_eq!(board.add_line((0, 1), Direction::Up, Color::Red), Ok(()));
+        assert!(!board.is_solved());
+    }
+
+    #[test]
+    fn merge_collinear_segments_collapses_a_straight_run() {
+        let mut board = Board::parse("RrrrrR").unwrap();
+        let lines = (0..5)
+            .map(|x| ((x, 0), Direction::Right, Color::Red))
+            .collect::<Vec<_>>();
+        board.import_lines(&lines).unwrap();
+        assert_eq!(
+            board.merge_collinear_segments(Color::Red),
+            vec![((0, 0), Direction::Right, 5)]
+        );
+    }
+
+    #[test]
+    fn merge_collinear_segments_splits_at_the_turns() {
+        let mut board = Board::parse("Rr\nrR").unwrap();
+        board.import_lines(&ZIGZAG).unwrap();
+        assert_eq!(
+            board.merge_collinear_segments(Color::Red),
+            vec![
+                ((0, 0), Direction::Right, 1),
+                ((1, 0), Direction::DownLeft, 1),
+                ((0, 1), Direction::Right, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn export_then_import_reproduces_the_lines_and_degrees() {
+        let text = "R2B\n2Gr\ngbR\n.GB";
+        let mut solved = Board::parse(text).unwrap();
+        solved.solve().unwrap();
+        let exported = solved.export_lines();
+
+        let mut board = Board::parse(text).unwrap();
+        board.import_lines(&exported).unwrap();
+        assert_eq!(board.export_lines(), exported);
+        assert_eq!(board.lines, solved.lines);
+        assert_eq!(board.degree, solved.degree);
+        assert_eq!(board.board, solved.board);
+        assert_eq!(board.validate_state(), Ok(()));
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn import_lines_leaves_the_board_untouched_on_an_illegal_line() {
+        let mut board = Board::parse("Rr\nrR").unwrap();
+        let mut lines = ZIGZAG.to_vec();
+        lines.push(((1, 1), Direction::Up, Color::Red));
+        let error = board.import_lines(&lines).unwrap_err();
+        assert_eq!(error.index, 3);
+        assert!(board.export_lines().is_empty());
+        assert_eq!(board.degree, vec![0; 4]);
+        assert!(board.board.iter().all(|&(_, count)| count == 0));
+    }
+
+    // everything a failed `add_line` or `remove_line` must leave as it was,
+    // its attempts counted in `stats` aside
+    #[allow(clippy::type_complexity)]
+    fn state(
+        board: &Board,
+    ) -> (
+        Vec<(Symbol, u8)>,
+        Vec<[Option<Color>; 4]>,
+        Vec<u8>,
+        Vec<(Point, Direction, Color)>,
+        Vec<usize>,
+        usize,
+        u64,
+        usize,
+    ) {
+        (
+            board.board.clone(),
+            board.lines.clone(),
+            board.degree.clone(),
+            board.result.clone(),
+            board.uncovered.clone(),
+            board.whites_left,
+            board.hash,
+            board.frozen,
+        )
+    }
+
+    fn rejects_add(board: &mut Board, line: (Point, Direction, Color), error: LineError) {
+        let before = state(board);
+        assert_eq!(board.add_line(line.0, line.1, line.2), Err(error));
+        assert_eq!(state(board), before, "{:?}", error);
+    }
+
+    fn rejects_remove(board: &mut Board, line: (Point, Direction), error: LineError) {
+        let before = state(board);
+        assert_eq!(board.remove_line(line.0, line.1), Err(error));
+        assert_eq!(state(board), before, "{:?}", error);
+    }
+
+    #[test]
+    fn each_line_error_leaves_the_board_untouched() {
+        let mut board = Board::parse("RrR\nBbB").unwrap();
+        board
+            .add_line((0, 0), Direction::Right, Color::Red)
+            .unwrap();
+        let red = Color::Red;
+        rejects_add(
+            &mut board,
+            ((0, 0), Direction::Left, red),
+            LineError::OutOfBounds,
+        );
+        rejects_add(
+            &mut board,
+            ((1, 0), Direction::Down, red),
+            LineError::ColorMismatch,
+        );
+        rejects_add(
+            &mut board,
+            ((2, 0), Direction::Left, red),
+            LineError::NodeFull,
+        );
+        rejects_add(
+            &mut board,
+            ((1, 0), Direction::Left, red),
+            LineError::AlreadyDrawn,
+        );
+        rejects_remove(
+            &mut board,
+            ((0, 0), Direction::Left),
+            LineError::OutOfBounds,
+        );
+        rejects_remove(&mut board, ((1, 1), Direction::Right), LineError::NotFound);
+        board.add_line((1, 0), Direction::Right, red).unwrap();
+        rejects_remove(&mut board, ((0, 0), Direction::Right), LineError::NotLast);
+
+        let mut white = Board::parse("R 2b R").unwrap();
+        rejects_add(
+            &mut white,
+            ((0, 0), Direction::Right, red),
+            LineError::ColorNotAllowed,
+        );
+
+        let mut crossing = Board::parse("Rb\nBr").unwrap();
+        crossing
+            .add_line((0, 0), Direction::DownRight, red)
+            .unwrap();
+        rejects_add(
+            &mut crossing,
+            ((1, 0), Direction::DownLeft, Color::Blue),
+            LineError::Crossing,
+        );
+    }
+
+    #[test]
+    fn fixed_lines_are_not_taken_back() {
+        let mut board = Board::parse("RrR").unwrap();
+        let before = state(&board);
+        let error = board
+            .fix_lines(&[((1, 0), Direction::Right, Color::Red)])
+            .unwrap_err();
+        assert_eq!(error.reason, LineError::NotOnPath);
+        assert_eq!(state(&board), before);
+
+        board
+            .fix_lines(&[((0, 0), Direction::Right, Color::Red)])
+            .unwrap();
+        rejects_remove(&mut board, ((0, 0), Direction::Right), LineError::Fixed);
+    }
+
+    #[test]
+    fn uncovered_nodes_follow_every_add_and_remove() {
+        let mut board = Board::parse("RrrR\nBbbB").unwrap();
+        let (red, blue) = (Color::Red.index(), Color::Blue.index());
+        assert_eq!((board.uncovered[red], board.uncovered[blue]), (2, 2));
+        board
+            .add_line((0, 0), Direction::Right, Color::Red)
+            .unwrap();
+        board
+            .add_line((1, 0), Direction::Right, Color::Red)
+            .unwrap();
+        assert_eq!((board.uncovered[red], board.uncovered[blue]), (0, 2));
+
+        // failed removals change nothing
+        rejects_remove(&mut board, ((0, 0), Direction::Right), LineError::NotLast);
+        rejects_remove(&mut board, ((1, 1), Direction::Right), LineError::NotFound);
+        assert_eq!((board.uncovered[red], board.uncovered[blue]), (0, 2));
+
+        board.remove_line((1, 0), Direction::Right).unwrap();
+        assert_eq!(board.uncovered[red], 1);
+        board.remove_line((0, 0), Direction::Right).unwrap();
+        assert_eq!((board.uncovered[red], board.uncovered[blue]), (2, 2));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{Board, ParseError};
+
+// the boards of `levels.txt`, each below a `# NAME` line
+const LEVELS: &str = include_str!("levels.txt");
+
+// A level embedded in the crate, named by the `# NAME` line heading it
+//
+// the crate ships no levels, see `levels.txt`, so `Level::all` is empty
+// unless boards are added there before building
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub name: &'static str,
+    // the board in the puzzle format, without the `#` lines heading it
+    pub text: &'static str,
+}
+
+impl Level {
+    // every level embedded, in the order of `levels.txt`
+    pub fn all() -> impl Iterator<Item = Level> {
+        LEVELS.split("\n\n").filter_map(Level::from_block)
+    }
+
+    // the level named `name`, whatever its case
+    pub fn find(name: &str) -> Option<Level> {
+        Level::all().find(|level| level.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn board(&self) -> Result<Board, ParseError> {
+        Board::parse(self.text)
+    }
+
+    // a block of `levels.txt` between blank lines, None for a block of
+    // comments only
+    fn from_block(block: &'static str) -> Option<Level> {
+        let mut name = None;
+        let mut rest = block.trim();
+        while let Some(comment) = rest.strip_prefix('#') {
+            let (line, next) = comment.split_once('\n').unwrap_or((comment, ""));
+            if name.is_none() && !line.trim().is_empty() {
+                name = Some(line.trim());
+            }
+            rest = next.trim_start();
+        }
+        let text = rest.trim_end();
+        (!text.is_empty()).then_some(Level { name: name?, text })
+    }
+}
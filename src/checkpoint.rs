@@ -0,0 +1,67 @@
+use log::{info, warn};
+use lyne::{SearchObserver, SearchSnapshot};
+use std::fs;
+use std::time::{Duration, Instant};
+
+// How often `--checkpoint` saves the search
+const INTERVAL: Duration = Duration::from_secs(60);
+// Moves between two looks at the clock
+const CLOCK_EVERY: u32 = 4096;
+
+// Saves the state of the search in a file for `--checkpoint`, every minute
+// and when the search gives up, or does nothing without a file
+pub struct Checkpointer {
+    path: Option<String>,
+    last: Instant,
+    moves: u32,
+}
+
+impl Checkpointer {
+    pub fn new(path: Option<String>) -> Self {
+        Checkpointer {
+            path,
+            last: Instant::now(),
+            moves: 0,
+        }
+    }
+
+    // forget the file of a search that ended on its own, which would only
+    // start it over at its end
+    pub fn finish(&self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl SearchObserver for Checkpointer {
+    fn wants_snapshot(&mut self, stopping: bool) -> bool {
+        if self.path.is_none() {
+            return false;
+        }
+        if stopping {
+            return true;
+        }
+        self.moves += 1;
+        if self.moves < CLOCK_EVERY {
+            return false;
+        }
+        self.moves = 0;
+        self.last.elapsed() >= INTERVAL
+    }
+
+    fn on_snapshot(&mut self, snapshot: SearchSnapshot) {
+        self.last = Instant::now();
+        let Some(path) = &self.path else {
+            return;
+        };
+        // a crash while writing leaves the previous snapshot whole
+        let temporary = format!("{}.tmp", path);
+        let written = fs::write(&temporary, format!("{}\n", snapshot.to_json()))
+            .and_then(|_| fs::rename(&temporary, path));
+        match written {
+            Ok(()) => info!("saved the search in {}", path),
+            Err(e) => warn!("failed to save the search in {}: {}", path, e),
+        }
+    }
+}
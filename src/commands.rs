@@ -0,0 +1,12 @@
+// The subcommands of `lyne`, each with the `run` taking the arguments after
+// its name and returning the exit code, or the error in the arguments
+pub mod bench;
+pub mod check;
+pub mod generate;
+pub mod hint;
+pub mod play;
+pub mod rate;
+pub mod solve_all;
+pub mod tui;
+pub mod verify;
+pub mod verify_unique;
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use crate::{report_error, solve_one, split_puzzles, Args, EXIT_INVALID_INPUT};
+
+// Times the search is run on each puzzle by default
+pub const DEFAULT_RUNS: usize = 10;
+
+// solve each puzzle read for `lyne bench` `runs` times, and print the
+// shortest, median, mean and longest time spent solving it
+//
+// return the exit code of the first puzzle not solved, or 0
+pub fn bench(args: &Args, runs: usize) -> i32 {
+    let input = match args.read_input() {
+        Ok(input) => input,
+        Err(e) => {
+            let name = args.source();
+            report_error(false, format!("failed to read {}: {}", name, e));
+            return EXIT_INVALID_INPUT;
+        }
+    };
+
+    let mut exit_code = 0;
+    for (i, puzzle) in split_puzzles(&input).iter().enumerate() {
+        let number = i + 1;
+        let mut times = Vec::with_capacity(runs);
+        let mut last = None;
+        for _ in 0..runs {
+            match solve_one(&puzzle.text, None, args) {
+                Ok(solved) => {
+                    times.push(solved.elapsed);
+                    last = Some(solved);
+                }
+                Err(errors) => {
+                    for e in errors {
+                        report_error(
+                            false,
+                            format!(
+                                "puzzle {} starting at line {}: {}",
+                                puzzle.describe(number, None),
+                                puzzle.line,
+                                e
+                            ),
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+        let Some(solved) = last else {
+            println!("Puzzle {}: invalid", puzzle.describe(number, None));
+            if exit_code == 0 {
+                exit_code = EXIT_INVALID_INPUT;
+            }
+            continue;
+        };
+        if exit_code == 0 {
+            exit_code = solved.exit_code(args.mode);
+        }
+
+        times.sort();
+        let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+        let mean = times.iter().sum::<Duration>() / times.len() as u32;
+        println!(
+            "Puzzle {}: {}, {} runs: min {:.3} ms, median {:.3} ms, mean {:.3} ms, max {:.3} ms",
+            puzzle.describe(number, Some((solved.board.width(), solved.board.height()))),
+            solved.status(args.mode),
+            times.len(),
+            ms(times[0]),
+            ms(times[times.len() / 2]),
+            ms(mean),
+            ms(times[times.len() - 1]),
+        );
+    }
+    exit_code
+}
+
+// time the search on the puzzles of a file for `lyne bench`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let mut runs = DEFAULT_RUNS;
+    let mut rest = Vec::new();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        if arg == "--runs" {
+            runs = match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) if n > 0 => n,
+                _ => return Err("--runs needs a positive number".into()),
+            };
+        } else {
+            rest.push(arg);
+        }
+    }
+    let args = Args::parse(rest.into_iter())?;
+    if args.output.is_some() || args.svg.is_some() {
+        return Err("bench only prints the times".into());
+    }
+    if args.partial.is_some() || args.verify.is_some() || args.rate || args.resume.is_some() {
+        return Err("bench only times the search".into());
+    }
+    if args.checkpoint.is_some() {
+        return Err("--checkpoint would slow down bench".into());
+    }
+    if args.visualize.is_some() || args.progress {
+        return Err("--visualize and --progress would slow down bench".into());
+    }
+    Ok(bench(&args, runs))
+}
@@ -0,0 +1,79 @@
+use lyne::{parse_segments, Json, PartialCheck};
+
+use crate::{
+    parse_puzzle, read_single_puzzle, report_error, report_errors, split_given, Args, Mode,
+    EXIT_INVALID_INPUT, EXIT_NO_SOLUTION, EXIT_TIMEOUT,
+};
+
+// tell whether the segments drawn on a puzzle can still be completed for
+// `lyne check`, and otherwise which of them made it impossible
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let args = Args::parse(args)?;
+    if args.mode != Mode::First || args.cross_check.is_some() {
+        return Err("check only searches the first solution".into());
+    }
+    if args.verify.is_some() || args.rate || args.visualize.is_some() || args.progress {
+        return Err("check only tells whether the segments can be completed".into());
+    }
+    let json = args.format.is_json();
+    let Some((input, partial)) = read_single_puzzle(&args, json) else {
+        return Ok(EXIT_INVALID_INPUT);
+    };
+    let (input, given) = split_given(&input, partial.as_deref(), &args.symbols)?;
+    let parsed = parse_puzzle(input, &args.symbols, args.pad).and_then(|board| {
+        parse_segments(given.unwrap_or(""))
+            .map(|segments| (board, segments))
+            .map_err(|e| vec![format!("given {}", e)])
+    });
+    let (mut board, segments) = match parsed {
+        Ok(parsed) => parsed,
+        Err(errors) => {
+            report_errors(json, errors);
+            return Ok(EXIT_INVALID_INPUT);
+        }
+    };
+    board.set_options(args.options.clone());
+    let check = match board.check_partial(&segments) {
+        Ok(check) => check,
+        Err(e) => {
+            report_error(json, format!("given {}", e));
+            return Ok(EXIT_INVALID_INPUT);
+        }
+    };
+
+    let dead_end = match check {
+        PartialCheck::DeadEnd(index) => Some(segments[index]),
+        _ => None,
+    };
+    if json {
+        let mut entries = vec![(
+            "extendable",
+            match check {
+                PartialCheck::Extendable(_) => true.into(),
+                PartialCheck::GaveUp => Json::Null,
+                _ => false.into(),
+            },
+        )];
+        if let Some(Json::Array(mut segment)) = dead_end.map(|s| Json::segments(&[s])) {
+            entries.push(("dead_end", segment.remove(0)));
+        }
+        println!("{}", Json::object(entries));
+    } else {
+        match check {
+            PartialCheck::Extendable(_) => println!("can be completed"),
+            PartialCheck::DeadEnd(_) => println!("cannot be completed"),
+            PartialCheck::Unsolvable => println!("the puzzle has no solution"),
+            PartialCheck::GaveUp => {
+                println!("unknown, the search gave up before the timeout or the attempt budget")
+            }
+        }
+        if let Some((point, direction, color)) = dead_end {
+            println!("dead end: {} {} {:?}", color, direction, point);
+        }
+    }
+    Ok(match check {
+        PartialCheck::Extendable(_) => 0,
+        PartialCheck::GaveUp => EXIT_TIMEOUT,
+        _ => EXIT_NO_SOLUTION,
+    })
+}
@@ -0,0 +1,67 @@
+use log::{error, info};
+use lyne::{Board, GenerateOptions};
+
+use crate::EXIT_NO_SOLUTION;
+
+// draw a random board for `lyne generate` and print it
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let size = args.next().ok_or("generate needs a size, e.g. 5x5")?;
+    let (width, height) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?)))
+        .filter(|&(w, h)| w > 0 && h > 0)
+        .ok_or(format!("invalid size {:?}, expected e.g. 5x5", size))?;
+    let mut options = GenerateOptions {
+        width,
+        height,
+        seed: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64),
+        ..GenerateOptions::default()
+    };
+    let mut count = 1;
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| {
+            args.next()
+                .and_then(|n| n.parse::<u64>().ok())
+                .ok_or(format!("{} needs a number", name))
+        };
+        match arg.as_str() {
+            "--colors" => match number("--colors")? {
+                n @ 1..=26 => options.colors = n as usize,
+                _ => return Err("--colors needs a number from 1 to 26".into()),
+            },
+            "--whites" => options.whites = number("--whites")? as usize,
+            "--seed" => options.seed = number("--seed")?,
+            "--min-effort" => options.min_effort = number("--min-effort")?,
+            "--unique" => options.unique = true,
+            "--count" => match number("--count")? {
+                0 => return Err("--count needs a positive number".into()),
+                n => count = n,
+            },
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    // each board is drawn with the next seed, and printed as soon as it is
+    // found, separated by blank lines like a batch of puzzles
+    for i in 0..count {
+        let options = GenerateOptions {
+            seed: options.seed.wrapping_add(i),
+            ..options.clone()
+        };
+        info!("generating with seed {}", options.seed);
+        let Some(board) = Board::generate(&options) else {
+            error!(
+                "no board fits the options after {} attempts",
+                options.attempts
+            );
+            return Ok(EXIT_NO_SOLUTION);
+        };
+        if i > 0 {
+            println!();
+        }
+        println!("{}", board);
+    }
+    Ok(0)
+}
@@ -0,0 +1,44 @@
+use lyne::Json;
+
+use crate::{report_error, solve_single_puzzle, Args, Mode, EXIT_INVALID_INPUT, EXIT_TIMEOUT};
+
+// print the next segment to draw on a puzzle partly solved by hand for
+// `lyne hint`, the segments drawn given after a `---` line or with `--partial`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let args = Args::parse(args)?;
+    if args.mode != Mode::First || args.cross_check.is_some() {
+        return Err("hint only searches the first solution".into());
+    }
+    if args.verify.is_some() || args.rate || args.visualize.is_some() {
+        return Err("hint only prints the next segment".into());
+    }
+    let json = args.format.is_json();
+    let Some(solved) = solve_single_puzzle(&args, json) else {
+        return Ok(EXIT_INVALID_INPUT);
+    };
+    let code = solved.exit_code(Mode::First);
+    let Some(solution) = solved.solutions.first() else {
+        let message = match code {
+            EXIT_TIMEOUT => "no solution found before the timeout or the attempt budget",
+            _ => "the segments drawn cannot be completed into a solution",
+        };
+        report_error(json, message);
+        solved.report_stats(&args);
+        return Ok(code);
+    };
+    let next = solution.next_segment();
+    if json {
+        let hint = match next.map(|s| Json::segments(&[s])) {
+            Some(Json::Array(mut segment)) => segment.remove(0),
+            _ => Json::Null,
+        };
+        println!("{}", Json::object([("hint", hint)]));
+    } else {
+        match next {
+            Some((point, direction, color)) => println!("{} {} {:?}", color, direction, point),
+            None => println!("solved, nothing left to draw"),
+        }
+    }
+    solved.report_stats(&args);
+    Ok(code)
+}
@@ -0,0 +1,235 @@
+use lyne::{Board, Color, Direction, LineError, Point, Symbol};
+use std::fs::File;
+use std::io::{self, BufRead, IsTerminal, Write};
+use strum::IntoEnumIterator;
+
+use crate::{prepare_single_puzzle, report_error, Args, EXIT_INVALID_INPUT, EXIT_TIMEOUT};
+
+const HELP: &str = "\
+r right 0,0    draw a Red line from (0,0) to the right, colors by letter or
+               name, directions as right, downright, down, downleft, left,
+               upleft, up, upright
+right          go on with the path drawn last, also with the arrow keys
+               followed by Enter
+undo           take back the last line drawn
+redo           draw again the last line taken back
+hint           draw the next line of a solution keeping yours
+show           draw the board again
+help           this message
+quit           give up";
+
+// A command of `lyne play`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    // a line from a node, or from the head of the path drawn last
+    Draw(Option<(Color, Point)>, Direction),
+    Undo,
+    Redo,
+    Hint,
+    Show,
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["undo" | "u"] => Some(Command::Undo),
+            ["redo"] => Some(Command::Redo),
+            ["hint"] => Some(Command::Hint),
+            ["show" | "s"] => Some(Command::Show),
+            ["help" | "h" | "?"] => Some(Command::Help),
+            ["quit" | "q"] => Some(Command::Quit),
+            [direction] => Some(Command::Draw(None, parse_direction(direction)?)),
+            [color, direction, ref point @ ..] if !point.is_empty() => {
+                let color = parse_color(color)?;
+                let point = parse_point(&point.concat())?;
+                Some(Command::Draw(
+                    Some((color, point)),
+                    parse_direction(direction)?,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+// a color by its letter or its name, in any case
+fn parse_color(word: &str) -> Option<Color> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => Color::from_letter(letter),
+        _ => Color::iter().find(|c| c.to_string().eq_ignore_ascii_case(word)),
+    }
+}
+
+// a direction by its name in any case, or the escape sequence of an arrow
+// key
+fn parse_direction(word: &str) -> Option<Direction> {
+    match word {
+        "\x1b[A" => Some(Direction::Up),
+        "\x1b[B" => Some(Direction::Down),
+        "\x1b[C" => Some(Direction::Right),
+        "\x1b[D" => Some(Direction::Left),
+        _ => {
+            let name = word.replace(['-', '_'], "");
+            Direction::iter().find(|d| d.to_string().eq_ignore_ascii_case(&name))
+        }
+    }
+}
+
+// `x,y` or `(x, y)`
+fn parse_point(text: &str) -> Option<Point> {
+    let text = text.trim_start_matches('(').trim_end_matches(')');
+    let (x, y) = text.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+// A puzzle played by hand, one line at a time
+pub struct Game {
+    // the puzzle without any line, to search for hints
+    puzzle: Board,
+    board: Board,
+    ansi: bool,
+}
+
+impl Game {
+    pub fn new(board: Board) -> Self {
+        Game {
+            puzzle: board.clone(),
+            board,
+            ansi: io::stdout().is_terminal(),
+        }
+    }
+
+    // play with the commands read from `input` until the puzzle is solved,
+    // the player quits or the input ends
+    //
+    // return whether the puzzle was solved
+    pub fn play(&mut self, input: impl BufRead, out: &mut dyn Write) -> io::Result<bool> {
+        writeln!(out, "{}", HELP)?;
+        self.show(out)?;
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Command::parse(line.trim()) {
+                Some(Command::Draw(from, direction)) => match self.draw(from, direction) {
+                    Ok(()) => self.show(out)?,
+                    Err(e) => writeln!(out, "cannot draw it: {}", e)?,
+                },
+                Some(Command::Undo) => match self.board.undo() {
+                    Some(_) => self.show(out)?,
+                    None => writeln!(out, "nothing to undo")?,
+                },
+                Some(Command::Redo) => match self.board.redo() {
+                    Some(_) => self.show(out)?,
+                    None => writeln!(out, "nothing to redo")?,
+                },
+                Some(Command::Hint) => {
+                    match self.puzzle.missing_segment(&self.board.export_lines()) {
+                        Some((pos, direction, color)) => {
+                            writeln!(out, "hint: {} {} {:?}", color, direction, pos)?;
+                            self.board
+                                .add_line(pos, direction, color)
+                                .expect("a hint is a legal line");
+                            self.show(out)?;
+                        }
+                        None => writeln!(out, "no solution keeps these lines, undo some")?,
+                    }
+                }
+                Some(Command::Show) => self.show(out)?,
+                Some(Command::Help) => writeln!(out, "{}", HELP)?,
+                Some(Command::Quit) => return Ok(false),
+                None => writeln!(out, "unknown command, help for the commands")?,
+            }
+            if self.board.check_solved().is_ok() {
+                writeln!(out, "solved!")?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // draw a line the way the game lets a player: the first line of a color
+    // leaves one of its endpoints, the next ones go on from where its path
+    // stops until it reaches the other endpoint
+    fn draw(&mut self, from: Option<(Color, Point)>, direction: Direction) -> Result<(), String> {
+        let (color, pos) = match from {
+            Some(from) => from,
+            None => {
+                let &(_, _, color) = self
+                    .board
+                    .export_lines()
+                    .last()
+                    .ok_or("no path to go on with, give a color and a node")?;
+                let head = self.head(color).ok_or(LineError::NotOnPath.to_string())?;
+                (color, head)
+            }
+        };
+        let (x, y) = pos;
+        if x < 0 || y < 0 || x >= self.board.width() as i32 || y >= self.board.height() as i32 {
+            return Err(LineError::OutOfBounds.to_string());
+        }
+        let on_path = match self.board.export_lines().iter().rfind(|l| l.2 == color) {
+            Some(_) => self.head(color) == Some(pos),
+            None => self.board.symbol(pos) == Some(Symbol::color_end(color)),
+        };
+        if !on_path {
+            return Err(LineError::NotOnPath.to_string());
+        }
+        self.board
+            .add_line(pos, direction, color)
+            .map_err(|e| e.to_string())
+    }
+
+    // where the path of `color` stops, or None if it is finished
+    fn head(&self, color: Color) -> Option<Point> {
+        let lines = self.board.export_lines();
+        let &(pos, direction, _) = lines.iter().rfind(|l| l.2 == color)?;
+        let head = direction.apply_offset(pos);
+        Some(head).filter(|&head| self.board.symbol(head) != Some(Symbol::color_end(color)))
+    }
+
+    fn show(&self, out: &mut dyn Write) -> io::Result<()> {
+        let head = self
+            .board
+            .export_lines()
+            .last()
+            .map(|&(pos, direction, _)| direction.apply_offset(pos));
+        if self.ansi {
+            write!(out, "{}", self.board.render_ansi(head))
+        } else {
+            write!(out, "{}", self.board.render())
+        }
+    }
+}
+
+// solve a puzzle by hand for `lyne play`, the options applying to the
+// search for hints
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let args = Args::parse(args)?;
+    let Some(board) = prepare_single_puzzle(&args, false) else {
+        return Ok(EXIT_INVALID_INPUT);
+    };
+    // stdin holds the puzzle without a file, the commands then come from
+    // the terminal
+    let commands: Box<dyn BufRead> = match &args.file {
+        Some(_) => Box::new(io::stdin().lock()),
+        None => match File::open("/dev/tty") {
+            Ok(tty) => Box::new(io::BufReader::new(tty)),
+            Err(_) => return Err("play needs a terminal to read its commands from".into()),
+        },
+    };
+    match Game::new(board).play(commands, &mut io::stdout()) {
+        Ok(true) => Ok(0),
+        // quitting proves nothing about the puzzle
+        Ok(false) => Ok(EXIT_TIMEOUT),
+        Err(e) => {
+            report_error(false, format!("failed to play: {}", e));
+            Ok(EXIT_INVALID_INPUT)
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use lyne::{RateError, SolveOptions};
+
+use crate::{read_board, report_error, Args, EXIT_INVALID_INPUT, EXIT_NO_SOLUTION, EXIT_TIMEOUT};
+
+// rate the difficulty of a puzzle for `lyne rate`, the same as `--rate`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let args = Args::parse(args)?;
+    let json = args.format.is_json();
+    let input = match args.read_input() {
+        Ok(input) => input,
+        Err(e) => {
+            let name = args.source();
+            report_error(json, format!("failed to read {}: {}", name, e));
+            return Ok(EXIT_INVALID_INPUT);
+        }
+    };
+    Ok(rate_one(&input, &args))
+}
+
+// rate the difficulty of the puzzle in `input` and print its measures
+pub fn rate_one(input: &str, args: &Args) -> i32 {
+    let json = args.format.is_json();
+    let mut board = match read_board(input, &args.symbols, args.pad) {
+        Ok(board) => board,
+        Err(e) => {
+            report_error(json, e.to_string());
+            return EXIT_INVALID_INPUT;
+        }
+    };
+    // the problems `validate` finds prove there is no solution
+    let report = board.validate();
+    if !report.is_valid() {
+        for e in report {
+            report_error(json, e.to_string());
+        }
+        return EXIT_NO_SOLUTION;
+    }
+    // only the timeout is taken from the options, see `SolveOptions::rating`
+    board.set_options(SolveOptions {
+        timeout: args.options.timeout,
+        max_attempts: args.options.max_attempts,
+        ..SolveOptions::rating()
+    });
+    match board.rate() {
+        Ok(difficulty) if json => println!("{}", difficulty.to_json()),
+        Ok(difficulty) => println!("{}", difficulty),
+        Err(RateError::NoSolution) => {
+            report_error(json, "no solution");
+            return EXIT_NO_SOLUTION;
+        }
+        Err(RateError::GaveUp) => {
+            report_error(json, "no rating before the timeout or the attempt budget");
+            return EXIT_TIMEOUT;
+        }
+    }
+    0
+}
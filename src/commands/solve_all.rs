@@ -0,0 +1,174 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::{
+    read_input, report_error, solve_one, split_puzzles, write_solutions, Args, Format,
+    EXIT_INVALID_INPUT,
+};
+
+// the `.txt` files under `dir` and its subdirectories, sorted by path
+fn puzzle_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(puzzle_files(&path)?);
+        } else if path.extension().is_some_and(|e| e == "txt") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// solve a puzzle file, writing the result to `<name>.solution` next to it
+//
+// return the status for the summary and the exit code, the problems found
+// being reported on stderr
+fn solve_file(path: &Path, args: &Args) -> (String, i32) {
+    let name = path.display();
+    let input = match read_input(path.to_str()) {
+        Ok(input) => input,
+        Err(e) => {
+            report_error(false, format!("failed to read {}: {}", name, e));
+            return ("unreadable".into(), EXIT_INVALID_INPUT);
+        }
+    };
+    let puzzles = split_puzzles(&input);
+    if puzzles.len() > 1 {
+        report_error(
+            false,
+            format!(
+                "{} holds {} puzzles, solve it on its own",
+                name,
+                puzzles.len()
+            ),
+        );
+        return ("invalid".into(), EXIT_INVALID_INPUT);
+    }
+    let input = puzzles
+        .first()
+        .map_or(input.as_str(), |puzzle| &puzzle.text);
+    let solved = match solve_one(input, None, args) {
+        Ok(solved) => solved,
+        Err(errors) => {
+            for e in errors {
+                report_error(false, format!("{}: {}", name, e));
+            }
+            return ("invalid".into(), EXIT_INVALID_INPUT);
+        }
+    };
+
+    let stats = (args.stats && args.format.is_json()).then(|| solved.stats_json());
+    let output = path.with_extension("solution");
+    let written = File::create(&output).and_then(|file| {
+        write_solutions(
+            &mut BufWriter::new(file),
+            &solved.board,
+            &solved.solutions,
+            args.mode,
+            args.format,
+            stats.as_ref(),
+        )
+    });
+    if let Err(e) = written {
+        report_error(
+            false,
+            format!("failed to write the result to {}: {}", output.display(), e),
+        );
+        return ("unwritable".into(), EXIT_INVALID_INPUT);
+    }
+    if args.stats && !args.format.is_json() {
+        eprintln!("{}:", name);
+        solved.report_stats(args);
+    }
+    let status = format!(
+        "{:<18} {:>10.3} ms",
+        solved.status(args.mode),
+        solved.elapsed.as_secs_f64() * 1000.0
+    );
+    (status, solved.exit_code(args.mode))
+}
+
+// solve every puzzle file under `dir` for `lyne solve-all`, and print a table
+// of the results with a summary
+//
+// return the exit code of the first puzzle not solved, or 0
+pub fn solve_all(dir: &Path, args: &Args) -> i32 {
+    if matches!(args.format, Format::Svg { .. } | Format::Drag { .. }) {
+        report_error(false, "solve-all writes text, segments or json");
+        return EXIT_INVALID_INPUT;
+    }
+    let files = match puzzle_files(dir) {
+        Ok(files) => files,
+        Err(e) => {
+            report_error(false, format!("failed to read {}: {}", dir.display(), e));
+            return EXIT_INVALID_INPUT;
+        }
+    };
+    // the solution files only list the paths
+    let args = &Args {
+        format: match args.format {
+            Format::Paths { .. } => Format::Paths { grid: false },
+            format => format,
+        },
+        ..args.clone()
+    };
+
+    let now = Instant::now();
+    let names = files
+        .iter()
+        .map(|path| path.strip_prefix(dir).unwrap_or(path).display().to_string())
+        .collect::<Vec<_>>();
+    let width = names
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max("puzzle".len());
+    println!("{:<width$}  {:<18} {:>13}", "puzzle", "result", "time");
+    let (mut solved_count, mut unsolved_count, mut error_count) = (0, 0, 0);
+    let mut exit_code = 0;
+    for (path, name) in files.iter().zip(&names) {
+        let (status, code) = solve_file(path, args);
+        match code {
+            0 => solved_count += 1,
+            EXIT_INVALID_INPUT => error_count += 1,
+            _ => unsolved_count += 1,
+        }
+        if exit_code == 0 {
+            exit_code = code;
+        }
+        println!("{:<width$}  {}", name, status);
+    }
+    println!(
+        "{} puzzles: {} solved, {} unsolved, {} invalid in {:.3} ms",
+        files.len(),
+        solved_count,
+        unsolved_count,
+        error_count,
+        now.elapsed().as_secs_f64() * 1000.0
+    );
+    exit_code
+}
+
+// solve the puzzle files of a directory for `lyne solve-all`
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let dir = args.next().ok_or("solve-all needs a directory")?;
+    let args = Args::parse(args)?;
+    if args.file.is_some() || args.output.is_some() || args.svg.is_some() {
+        return Err("solve-all writes the solutions next to the puzzles".into());
+    }
+    if args.partial.is_some() || args.verify.is_some() || args.rate {
+        return Err("--partial, --verify and --rate only take a single puzzle".into());
+    }
+    if args.checkpoint.is_some() || args.resume.is_some() {
+        return Err("--checkpoint and --resume only take a single puzzle".into());
+    }
+    if args.visualize.is_some() || args.progress {
+        return Err("--visualize and --progress only follow a single puzzle".into());
+    }
+    Ok(solve_all(Path::new(&dir), &args))
+}
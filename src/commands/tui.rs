@@ -0,0 +1,229 @@
+use lyne::{Board, CancellationToken, Color, Direction, Point, SearchObserver, Symbol};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::visualize::Pace;
+use crate::{
+    prepare_single_puzzle, report_error, write_result, Args, Mode, Solved, DEFAULT_FPS,
+    EXIT_INVALID_INPUT, EXIT_TIMEOUT,
+};
+
+const HELP: &str = "\
+Enter or s: next move   c: run, Enter to pause again   i: lines and counts
+q: quit                 h: this help";
+
+// What the search does between two moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // waiting for a command after each move
+    Paused,
+    // drawing at most `fps` frames per second until a command comes
+    Running,
+    // cancelled, the search stopping at its next move
+    Quit,
+}
+
+// The interactive view of `lyne tui`: it observes the search, redraws the
+// board on stderr and takes commands from the terminal, one per line, read
+// on another thread so that a running search only checks for them
+pub struct Session {
+    state: State,
+    commands: Receiver<String>,
+    token: CancellationToken,
+    interval: Duration,
+    last_frame: Option<Instant>,
+    // shown below the board until the next move
+    message: String,
+}
+
+impl Session {
+    // a session cancelling `token` when asked to quit, or None without a
+    // terminal to read the commands from
+    pub fn new(token: CancellationToken, fps: f64) -> Option<Self> {
+        let tty = BufReader::new(File::open("/dev/tty").ok()?);
+        let (send, commands) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in tty.lines() {
+                let Ok(line) = line else { break };
+                if send.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(Session {
+            state: State::Paused,
+            commands,
+            token,
+            interval: Duration::from_secs_f64(1.0 / fps),
+            last_frame: None,
+            message: HELP.into(),
+        })
+    }
+
+    // draw the last state of the search with its verdict, which stays on
+    // the screen
+    pub fn finish(&mut self, board: &Board, verdict: &str) {
+        self.message.clear();
+        self.draw(board, None, verdict);
+    }
+
+    fn draw(&mut self, board: &Board, head: Option<Point>, status: &str) {
+        // move to the top left corner and clear the screen
+        let mut frame = format!("\x1b[H\x1b[2J{}{}\n", board.render_ansi(head), status);
+        if !self.message.is_empty() {
+            frame.push_str(&self.message);
+            frame.push('\n');
+        }
+        let _ = io::stderr().write_all(frame.as_bytes());
+        self.last_frame = Some(Instant::now());
+    }
+
+    fn on_move(&mut self, board: &Board, head: Point, event: String) {
+        match self.state {
+            State::Quit => return,
+            State::Running => match self.commands.try_recv() {
+                Ok(_) => self.state = State::Paused,
+                Err(TryRecvError::Empty) => {
+                    if self
+                        .last_frame
+                        .is_none_or(|last| last.elapsed() >= self.interval)
+                    {
+                        self.draw(board, Some(head), &format!("running, {}", event));
+                    }
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => return,
+            },
+            State::Paused => {}
+        }
+        let status = format!("paused, {}", event);
+        self.draw(board, Some(head), &status);
+        self.message.clear();
+        loop {
+            // without a terminal any more, the search runs to its end
+            let Ok(command) = self.commands.recv() else {
+                self.state = State::Running;
+                return;
+            };
+            match command.trim() {
+                "" | "s" => return,
+                "c" => {
+                    self.state = State::Running;
+                    return;
+                }
+                "q" => {
+                    self.token.cancel();
+                    self.state = State::Quit;
+                    return;
+                }
+                "i" => self.message = inspect(board),
+                _ => self.message = HELP.into(),
+            }
+            self.draw(board, Some(head), &status);
+            self.message.clear();
+        }
+    }
+}
+
+impl SearchObserver for Session {
+    fn on_place(&mut self, board: &Board, pos: Point, direction: Direction, color: Color) {
+        let head = direction.apply_offset(pos);
+        let event = format!("drew {} {} from {:?}", color, direction, pos);
+        self.on_move(board, head, event);
+    }
+
+    fn on_remove(&mut self, board: &Board, pos: Point, direction: Direction, color: Color) {
+        let event = format!("took back {} {} from {:?}", color, direction, pos);
+        self.on_move(board, pos, event);
+    }
+
+    fn on_color_completed(&mut self, _board: &Board, color: Color) {
+        self.message = format!("{} is connected", color);
+    }
+}
+
+// the lines on the board in the order they were drawn, and the number of
+// lines touching each node
+fn inspect(board: &Board) -> String {
+    let lines = board.export_lines();
+    let mut text = format!("{} lines:", lines.len());
+    for (pos, direction, color) in lines {
+        text.push_str(&format!("\n  {} {} {:?}", color, direction, pos));
+    }
+    text.push_str("\nlines at each node:");
+    for y in 0..board.height() as i32 {
+        text.push_str("\n ");
+        for x in 0..board.width() as i32 {
+            match (board.symbol((x, y)), board.lines_at((x, y))) {
+                (Some(symbol), Some(lines)) if symbol != Symbol::Empty => {
+                    text.push_str(&format!(" {}", lines))
+                }
+                _ => text.push_str(" ."),
+            }
+        }
+    }
+    text
+}
+
+// follow the search for the first solution of a puzzle interactively for
+// `lyne tui`, then print the solution like `lyne solve`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let mut args = Args::parse(args)?;
+    if args.mode != Mode::First || args.cross_check.is_some() {
+        return Err("tui only searches the first solution".into());
+    }
+    if args.verify.is_some() || args.rate || args.progress {
+        return Err("tui only follows the search".into());
+    }
+    // the pace of `--visualize` is the speed of the search when running
+    let fps = match args.visualize {
+        Some(Pace::Fps(fps)) => fps,
+        _ => DEFAULT_FPS,
+    };
+    let token = CancellationToken::new();
+    args.options.cancellation = Some(token.clone());
+    // the session stops the search itself
+    args.options.threads = 1;
+
+    let Some(mut board) = prepare_single_puzzle(&args, false) else {
+        return Ok(EXIT_INVALID_INPUT);
+    };
+    let Some(mut session) = Session::new(token, fps) else {
+        return Err("tui needs a terminal to read its commands from".into());
+    };
+
+    let now = Instant::now();
+    let solution = board.solve_observed(&mut session);
+    let elapsed = now.elapsed();
+    let verdict = match &solution {
+        Some(_) => "solved",
+        None if board.stats().cancelled => "quit",
+        None if board.stats().timed_out => "timed out",
+        None => "no solution",
+    };
+    session.finish(&board, verdict);
+
+    let solved = Solved {
+        board,
+        solutions: solution.into_iter().collect(),
+        elapsed,
+    };
+    if let [solution] = &solved.solutions[..] {
+        if let Err(e) = write_result(
+            &mut io::stdout(),
+            &solved.board,
+            solution,
+            args.format,
+            None,
+        ) {
+            report_error(false, format!("failed to write the solution: {}", e));
+        }
+    }
+    solved.report_stats(&args);
+    if solved.board.stats().cancelled {
+        return Ok(EXIT_TIMEOUT);
+    }
+    Ok(solved.exit_code(Mode::First))
+}
@@ -0,0 +1,91 @@
+use lyne::{parse_segments, Alphabet, Json, VerifyError};
+
+use crate::{
+    parse_puzzle, read_alphabet, read_input, report_error, report_errors, EXIT_INVALID_INPUT,
+    EXIT_NO_SOLUTION,
+};
+
+// check a solution file against a puzzle file for `lyne verify`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let mut files = Vec::new();
+    let mut json = false;
+    let mut symbols = Alphabet::default();
+    let mut pad = false;
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().as_deref() {
+                Some("text") => json = false,
+                Some("json") => json = true,
+                _ => return Err("verify prints text or json".into()),
+            },
+            "--symbols" => {
+                symbols = read_alphabet(&args.next().ok_or("--symbols needs a file path")?)?
+            }
+            "--pad" => pad = true,
+            _ if !arg.starts_with('-') && files.len() < 2 => files.push(arg),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    let [puzzle, solution] = &files[..] else {
+        return Err("verify needs a puzzle file and a solution file".into());
+    };
+    let read = |path: &String| {
+        read_input(Some(path)).map_err(|e| format!("failed to read {}: {}", path, e))
+    };
+    let (input, proposed) = match (read(puzzle), read(solution)) {
+        (Ok(input), Ok(proposed)) => (input, proposed),
+        (Err(e), _) | (_, Err(e)) => {
+            report_error(json, e);
+            return Ok(EXIT_INVALID_INPUT);
+        }
+    };
+    Ok(verify_one(&input, &proposed, &symbols, pad, json))
+}
+
+// check the segments of `proposed` against the puzzle in `input`, printing
+// whether they solve it or the first problem met
+pub fn verify_one(input: &str, proposed: &str, symbols: &Alphabet, pad: bool, json: bool) -> i32 {
+    let problems = parse_puzzle(input, symbols, pad).and_then(|board| {
+        parse_segments(proposed)
+            .map(|segments| (board, segments))
+            .map_err(|e| vec![format!("solution {}", e)])
+    });
+    let (mut board, segments) = match problems {
+        Ok(parsed) => parsed,
+        Err(errors) => {
+            report_errors(json, errors);
+            return EXIT_INVALID_INPUT;
+        }
+    };
+
+    let puzzle = board.clone();
+    let result = board.verify(&segments);
+    // legal segments that do not solve the puzzle yet may only miss some
+    let missing = match &result {
+        Err(VerifyError::IllegalSegment(_) | VerifyError::State(_)) | Ok(()) => None,
+        Err(_) => puzzle.missing_segment(&segments),
+    };
+    if json {
+        let mut entries = vec![("valid", result.is_ok().into())];
+        if let Err(e) = &result {
+            entries.push(("error", e.to_string().into()));
+        }
+        if let Some(Json::Array(mut segment)) = missing.map(|s| Json::segments(&[s])) {
+            entries.push(("missing", segment.remove(0)));
+        }
+        println!("{}", Json::object(entries));
+    } else {
+        match &result {
+            Ok(()) => println!("valid"),
+            Err(e) => println!("invalid: {}", e),
+        }
+        if let Some((point, direction, color)) = missing {
+            println!("missing: {} {} {:?}", color, direction, point);
+        }
+    }
+    match result {
+        Ok(()) => 0,
+        Err(_) => EXIT_NO_SOLUTION,
+    }
+}
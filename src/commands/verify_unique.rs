@@ -0,0 +1,28 @@
+use crate::{
+    solve_single_puzzle, Args, Mode, EXIT_INVALID_INPUT, EXIT_MULTIPLE_SOLUTIONS, EXIT_NO_SOLUTION,
+};
+
+// tell whether a puzzle has a single solution for `lyne verify-unique`,
+// printing UNIQUE, MULTIPLE or UNSOLVABLE with the exit code of `--unique`
+pub fn run(args: impl Iterator<Item = String>) -> Result<i32, String> {
+    let args = Args {
+        mode: Mode::Unique,
+        ..Args::parse(args)?
+    };
+    if args.partial.is_some() || args.verify.is_some() || args.rate || args.visualize.is_some() {
+        return Err("verify-unique only counts the solutions".into());
+    }
+    let Some(solved) = solve_single_puzzle(&args, false) else {
+        return Ok(EXIT_INVALID_INPUT);
+    };
+    let code = solved.exit_code(Mode::Unique);
+    let verdict = match code {
+        0 => "UNIQUE",
+        EXIT_NO_SOLUTION => "UNSOLVABLE",
+        EXIT_MULTIPLE_SOLUTIONS => "MULTIPLE",
+        _ => "TIMED OUT",
+    };
+    println!("{}", verdict);
+    solved.report_stats(&args);
+    Ok(code)
+}
@@ -0,0 +1,255 @@
+use strum_macros::Display;
+
+use crate::{Algorithm, Board, Color, DirectionOrder, Solution, SolveOptions, Stats, Symbol};
+
+// Distinct solutions counted by `Board::rate`
+const RATE_SOLUTIONS: usize = 10;
+
+// How hard a board is, by its score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Rating {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Brutal,
+}
+
+impl Rating {
+    fn of(score: f64) -> Self {
+        match score {
+            s if s < 2.0 => Rating::Trivial,
+            s if s < 3.0 => Rating::Easy,
+            s if s < 4.5 => Rating::Medium,
+            s if s < 6.0 => Rating::Hard,
+            _ => Rating::Brutal,
+        }
+    }
+}
+
+// Why `Board::rate` has no difficulty for a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateError {
+    // the search proved there is no solution
+    NoSolution,
+    // the search gave up on its timeout, attempt budget or cancellation
+    GaveUp,
+}
+
+impl std::fmt::Display for RateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateError::NoSolution => write!(f, "no solution"),
+            RateError::GaveUp => write!(f, "no rating before the search gave up"),
+        }
+    }
+}
+
+impl std::error::Error for RateError {}
+
+// The measures a difficulty estimate is made of
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    // calls to `add_line` of the search, legal or not
+    pub attempts: u64,
+    pub backtracks: u64,
+    // the most lines on the board at once
+    pub max_depth: usize,
+    // line attempts per line placed, how many moves the search weighs at
+    // each step on average
+    pub branching: f64,
+    // number of segments of each color in the solution
+    pub path_lengths: Vec<(Color, usize)>,
+    pub whites: usize,
+    // the sum of the numbers of the white nodes
+    pub white_passes: u32,
+    // distinct solutions, when they were counted
+    pub solutions: Option<usize>,
+    pub score: f64,
+    pub rating: Rating,
+}
+
+impl Difficulty {
+    // take the number of distinct solutions into account, several solutions
+    // making a board easier
+    pub fn with_solutions(mut self, solutions: usize) -> Self {
+        self.solutions = Some(solutions);
+        self.rate();
+        self
+    }
+
+    // the score grows with the logarithm of the attempts, so each bucket
+    // needs about ten times more search than the previous one, and a little
+    // with the passes through white nodes
+    fn rate(&mut self) {
+        let mut score = (1.0 + self.attempts as f64).log10() + 0.1 * self.white_passes as f64;
+        if let Some(solutions) = self.solutions.filter(|&n| n > 1) {
+            score -= (solutions as f64).log10();
+        }
+        self.score = score.max(0.0);
+        self.rating = Rating::of(self.score);
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "attempts: {}", self.attempts)?;
+        writeln!(f, "backtracks: {}", self.backtracks)?;
+        writeln!(f, "max depth: {}", self.max_depth)?;
+        writeln!(f, "branching: {:.2}", self.branching)?;
+        let lengths = self
+            .path_lengths
+            .iter()
+            .map(|(color, length)| format!("{} {}", color, length))
+            .collect::<Vec<_>>();
+        writeln!(f, "path lengths: {}", lengths.join(", "))?;
+        writeln!(
+            f,
+            "white passes: {} on {} nodes",
+            self.white_passes, self.whites
+        )?;
+        if let Some(solutions) = self.solutions {
+            writeln!(f, "solutions: {}", solutions)?;
+        }
+        writeln!(f, "score: {:.2}", self.score)?;
+        write!(f, "rating: {}", self.rating)
+    }
+}
+
+impl SolveOptions {
+    // the options `Board::rate` searches with, pinned so that improving the
+    // heuristics does not change the rating of a board: no pruning, no
+    // symmetry or transposition table, colors by fewest nodes first and
+    // directions in their fixed order, on a single thread
+    pub fn rating() -> Self {
+        SolveOptions {
+            algorithm: Algorithm::Dfs,
+            prune: false,
+            color_order: None,
+            direction_order: DirectionOrder::Fixed,
+            threads: 1,
+            timeout: None,
+            max_attempts: None,
+            transposition_size: 0,
+            restart_attempts: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl Solution {
+    // estimate the difficulty of `board` from this solution and the work done
+    // to find it, only comparable between searches run with
+    // `SolveOptions::rating`
+    pub fn difficulty(&self, board: &Board, stats: &Stats) -> Difficulty {
+        let whites = board
+            .symbols()
+            .filter_map(|s| match s {
+                Symbol::White(n) => Some(n),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut difficulty = Difficulty {
+            attempts: stats.add_line_calls,
+            backtracks: stats.backtracks,
+            max_depth: stats.max_depth,
+            branching: stats.add_line_calls as f64 / stats.lines_placed.max(1) as f64,
+            path_lengths: board
+                .colors()
+                .iter()
+                .map(|&color| (color, self.segments_for(color).count()))
+                .collect(),
+            whites: whites.len(),
+            white_passes: whites.iter().map(|&n| u32::from(n)).sum(),
+            solutions: None,
+            score: 0.0,
+            rating: Rating::Trivial,
+        };
+        difficulty.rate();
+        difficulty
+    }
+}
+
+impl Board {
+    // solve a copy of the board with `SolveOptions::rating`, keeping its
+    // timeout, attempt budget and cancellation token, and rate it with the
+    // number of its solutions up to 10
+    //
+    // fail with `RateError::GaveUp` if either search gave up before its end
+    pub fn rate(&self) -> Result<Difficulty, RateError> {
+        let mut board = self.clone();
+        board.set_options(SolveOptions {
+            timeout: self.options.timeout,
+            max_attempts: self.options.max_attempts,
+            cancellation: self.options.cancellation.clone(),
+            ..SolveOptions::rating()
+        });
+        let gave_up = |board: &Board| board.stats().timed_out || board.stats().cancelled;
+        let Some(solution) = board.solve() else {
+            return Err(if gave_up(&board) {
+                RateError::GaveUp
+            } else {
+                RateError::NoSolution
+            });
+        };
+        let difficulty = solution.difficulty(&board, board.stats());
+
+        let mut board = self.clone();
+        board.set_options(SolveOptions {
+            timeout: self.options.timeout,
+            max_attempts: self.options.max_attempts,
+            cancellation: self.options.cancellation.clone(),
+            ..SolveOptions::rating()
+        });
+        let solutions = board.solve_all(Some(RATE_SOLUTIONS)).len();
+        if gave_up(&board) {
+            return Err(RateError::GaveUp);
+        }
+        Ok(difficulty.with_solutions(solutions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_easy_board_rates_below_a_hard_one() {
+        let easy = Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap().rate().unwrap();
+        let hard = Board::parse("bb2R.\nb222R\nB32Br\nG222.\nGgggg")
+            .unwrap()
+            .rate()
+            .unwrap();
+        assert!(easy.attempts < hard.attempts);
+        assert!(easy.score < hard.score);
+        assert_eq!(easy.rating, Rating::Easy);
+        assert_eq!(hard.rating, Rating::Hard);
+    }
+
+    #[test]
+    fn the_rating_ignores_the_options_of_the_board() {
+        let text = "R2B\n2Gr\ngbR\n.GB";
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            direction_order: DirectionOrder::Greedy,
+            prune: true,
+            threads: 4,
+            ..SolveOptions::default()
+        });
+        assert_eq!(board.rate(), Board::parse(text).unwrap().rate());
+    }
+
+    #[test]
+    fn no_solution_and_giving_up_are_told_apart() {
+        let unsolvable = Board::parse("RbR\nBrB").unwrap();
+        assert_eq!(unsolvable.rate(), Err(RateError::NoSolution));
+
+        let mut limited = Board::parse("bb2R.\nb222R\nB32Br\nG222.\nGgggg").unwrap();
+        limited.set_options(SolveOptions {
+            max_attempts: Some(10),
+            ..SolveOptions::default()
+        });
+        assert_eq!(limited.rate(), Err(RateError::GaveUp));
+    }
+}
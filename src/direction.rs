@@ -0,0 +1,109 @@
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter};
+
+use crate::Point;
+
+// Only store these 4 directions
+// The other 4 are just the reverse of these
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DirectionInner {
+    Right = 0,
+    DownRight = 1,
+    Down = 2,
+    DownLeft = 3,
+}
+
+impl From<u8> for DirectionInner {
+    fn from(d: u8) -> Self {
+        match d {
+            0 => DirectionInner::Right,
+            1 => DirectionInner::DownRight,
+            2 => DirectionInner::Down,
+            3 => DirectionInner::DownLeft,
+            _ => panic!("invalid direction: {}", d),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display, EnumIter)]
+pub enum Direction {
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+    Up,
+    UpRight,
+}
+
+impl From<DirectionInner> for Direction {
+    fn from(d: DirectionInner) -> Self {
+        match d {
+            DirectionInner::Right => Direction::Right,
+            DirectionInner::DownRight => Direction::DownRight,
+            DirectionInner::Down => Direction::Down,
+            DirectionInner::DownLeft => Direction::DownLeft,
+        }
+    }
+}
+
+impl Direction {
+    pub fn to_inner(self) -> (DirectionInner, bool) {
+        match self {
+            Direction::Up => (DirectionInner::Down, true),
+            Direction::Down => (DirectionInner::Down, false),
+            Direction::Left => (DirectionInner::Right, true),
+            Direction::Right => (DirectionInner::Right, false),
+            Direction::UpRight => (DirectionInner::DownLeft, true),
+            Direction::UpLeft => (DirectionInner::DownRight, true),
+            Direction::DownRight => (DirectionInner::DownRight, false),
+            Direction::DownLeft => (DirectionInner::DownLeft, false),
+        }
+    }
+
+    pub fn store(self, pos: Point) -> (Point, DirectionInner) {
+        let (direction_inner, reverse) = self.to_inner();
+        if reverse {
+            (self.apply_offset(pos), direction_inner)
+        } else {
+            (pos, direction_inner)
+        }
+    }
+
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::UpRight => (1, -1),
+            Direction::Right => (1, 0),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (0, 1),
+            Direction::DownLeft => (-1, 1),
+            Direction::Left => (-1, 0),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+
+    pub fn from_offset(offset: (i32, i32)) -> Option<Self> {
+        Direction::iter().find(|d| d.offset() == offset)
+    }
+
+    pub fn apply_offset(self, (x, y): Point) -> Point {
+        let offset = self.offset();
+        (x + offset.0, y + offset.1)
+    }
+
+    // Beveled edges cannot cross each other
+    pub fn may_conflict(self, (x, y): Point) -> Option<(Point, DirectionInner)> {
+        match self {
+            Direction::Up => None,
+            Direction::Down => None,
+            Direction::Left => None,
+            Direction::Right => None,
+            Direction::UpRight => Some(((x, y - 1), DirectionInner::DownRight)),
+            Direction::UpLeft => Some(((x, y - 1), DirectionInner::DownLeft)),
+            Direction::DownRight => Some(((x + 1, y), DirectionInner::DownLeft)),
+            Direction::DownLeft => Some(((x - 1, y), DirectionInner::DownRight)),
+        }
+    }
+}
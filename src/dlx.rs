@@ -0,0 +1,510 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use strum::IntoEnumIterator;
+
+use crate::solver::{cancelled, out_of_budget, with_timeout};
+use crate::{node_order, Board, Color, Direction, DirectionInner, Point, Stats, Symbol};
+
+// The value of an edge item for a line left out, above every color
+const UNUSED: i32 = 255;
+
+// An exact cover problem with color controlled secondary items, solved with
+// Knuth's dancing links (Algorithm C of The Art of Computer Programming,
+// 7.2.2.1)
+//
+// items 1 to `primary` are covered exactly once, the others at most once, or
+// by any number of options giving them the same color; nodes after the item
+// headers hold the options, separated by spacers whose `top` is not positive
+struct Links {
+    llink: Vec<usize>,
+    rlink: Vec<usize>,
+    len: Vec<usize>,
+    ulink: Vec<usize>,
+    dlink: Vec<usize>,
+    top: Vec<isize>,
+    color: Vec<i32>,
+    // the option of each node
+    row: Vec<usize>,
+}
+
+impl Links {
+    // `options` lists the items of each option with their color, 0 for none
+    fn new(primary: usize, secondary: usize, options: &[Vec<(usize, i32)>]) -> Self {
+        let items = primary + secondary;
+        let mut links = Links {
+            llink: (0..=items).collect(),
+            rlink: (0..=items).collect(),
+            len: vec![0; items + 1],
+            ulink: (0..=items).collect(),
+            dlink: (0..=items).collect(),
+            top: vec![0; items + 1],
+            color: vec![0; items + 1],
+            row: vec![usize::MAX; items + 1],
+        };
+        // only the primary items are linked to the root, the secondary ones
+        // are never chosen
+        for i in 0..=primary {
+            links.llink[i] = if i == 0 { primary } else { i - 1 };
+            links.rlink[i] = if i == primary { 0 } else { i + 1 };
+        }
+        let mut spacer = links.push(0, 0, usize::MAX);
+        for (row, option) in options.iter().enumerate() {
+            let first = links.top.len();
+            for &(item, color) in option {
+                let x = links.push(item as isize, color, row);
+                let last = links.ulink[item];
+                links.ulink[x] = last;
+                links.dlink[x] = item;
+                links.dlink[last] = x;
+                links.ulink[item] = x;
+                links.len[item] += 1;
+            }
+            links.dlink[spacer] = links.top.len() - 1;
+            spacer = links.push(-(row as isize) - 1, 0, usize::MAX);
+            links.ulink[spacer] = first;
+        }
+        links
+    }
+
+    fn push(&mut self, top: isize, color: i32, row: usize) -> usize {
+        let x = self.top.len();
+        self.ulink.push(x);
+        self.dlink.push(x);
+        self.top.push(top);
+        self.color.push(color);
+        self.row.push(row);
+        x
+    }
+
+    // take the other nodes of the option of `p` out of their items
+    fn hide(&mut self, p: usize) {
+        let mut q = p + 1;
+        while q != p {
+            let x = self.top[q];
+            if x <= 0 {
+                q = self.ulink[q];
+                continue;
+            }
+            if self.color[q] >= 0 {
+                let (u, d) = (self.ulink[q], self.dlink[q]);
+                self.dlink[u] = d;
+                self.ulink[d] = u;
+                self.len[x as usize] -= 1;
+            }
+            q += 1;
+        }
+    }
+
+    fn unhide(&mut self, p: usize) {
+        let mut q = p - 1;
+        while q != p {
+            let x = self.top[q];
+            if x <= 0 {
+                q = self.dlink[q];
+                continue;
+            }
+            if self.color[q] >= 0 {
+                let (u, d) = (self.ulink[q], self.dlink[q]);
+                self.dlink[u] = q;
+                self.ulink[d] = q;
+                self.len[x as usize] += 1;
+            }
+            q -= 1;
+        }
+    }
+
+    fn cover(&mut self, i: usize) {
+        let mut p = self.dlink[i];
+        while p != i {
+            self.hide(p);
+            p = self.dlink[p];
+        }
+        let (l, r) = (self.llink[i], self.rlink[i]);
+        self.rlink[l] = r;
+        self.llink[r] = l;
+    }
+
+    fn uncover(&mut self, i: usize) {
+        let (l, r) = (self.llink[i], self.rlink[i]);
+        self.rlink[l] = i;
+        self.llink[r] = i;
+        let mut p = self.ulink[i];
+        while p != i {
+            self.unhide(p);
+            p = self.ulink[p];
+        }
+    }
+
+    // keep only the options giving the item of `p` its color
+    fn purify(&mut self, p: usize) {
+        let (c, i) = (self.color[p], self.top[p] as usize);
+        let mut q = self.dlink[i];
+        while q != i {
+            if self.color[q] != c {
+                self.hide(q);
+            } else if q != p {
+                self.color[q] = -1;
+            }
+            q = self.dlink[q];
+        }
+    }
+
+    fn unpurify(&mut self, p: usize) {
+        let (c, i) = (self.color[p], self.top[p] as usize);
+        let mut q = self.ulink[i];
+        while q != i {
+            if self.color[q] < 0 {
+                self.color[q] = c;
+            } else if q != p {
+                self.unhide(q);
+            }
+            q = self.ulink[q];
+        }
+    }
+
+    fn commit(&mut self, p: usize, j: usize) {
+        match self.color[p] {
+            0 => self.cover(j),
+            c if c > 0 => self.purify(p),
+            _ => {}
+        }
+    }
+
+    fn uncommit(&mut self, p: usize, j: usize) {
+        match self.color[p] {
+            0 => self.uncover(j),
+            c if c > 0 => self.unpurify(p),
+            _ => {}
+        }
+    }
+
+    // choose options covering every primary item, the item with the fewest
+    // options left first, calling `tried` before each option and `found` with
+    // every cover
+    //
+    // return whether one of them stopped the search
+    fn search(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        tried: &mut dyn FnMut() -> bool,
+        found: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if self.rlink[0] == 0 {
+            return found(chosen);
+        }
+        let mut item = self.rlink[0];
+        let mut i = item;
+        while i != 0 {
+            if self.len[i] < self.len[item] {
+                item = i;
+            }
+            i = self.rlink[i];
+        }
+        self.cover(item);
+        let mut stop = false;
+        let mut x = self.dlink[item];
+        while x != item {
+            if tried() {
+                stop = true;
+                break;
+            }
+            let mut p = x + 1;
+            while p != x {
+                let j = self.top[p];
+                if j <= 0 {
+                    p = self.ulink[p];
+                } else {
+                    self.commit(p, j as usize);
+                    p += 1;
+                }
+            }
+            chosen.push(self.row[x]);
+            stop = self.search(chosen, tried, found);
+            chosen.pop();
+            let mut p = x - 1;
+            while p != x {
+                let j = self.top[p];
+                if j <= 0 {
+                    p = self.dlink[p];
+                } else {
+                    self.uncommit(p, j as usize);
+                    p -= 1;
+                }
+            }
+            if stop {
+                break;
+            }
+            x = self.dlink[x];
+        }
+        self.uncover(item);
+        stop
+    }
+}
+
+// The exact cover problem of a board: every node is a primary item, covered
+// by the option of its lines and their colors; every line that can be drawn
+// is a secondary item colored by the options of the nodes at both of its
+// ends, so that they agree; every pair of crossing diagonals is a secondary
+// item covered by the option drawing either of them
+struct Cover {
+    primary: usize,
+    secondary: usize,
+    options: Vec<Vec<(usize, i32)>>,
+    // the lines drawn by each option, by their stored position, each only
+    // in the option of the node storing it
+    lines: Vec<Vec<(usize, DirectionInner, Color)>>,
+}
+
+impl Cover {
+    fn new(board: &Board) -> Self {
+        let nodes = (0..board.board.len())
+            .filter(|&i| board.board[i].0 != Symbol::Empty)
+            .collect::<Vec<_>>();
+        let primary = nodes.len();
+        let mut items: HashMap<(usize, Option<DirectionInner>), usize> = HashMap::new();
+        let mut item = |key| {
+            let next = primary + items.len() + 1;
+            *items.entry(key).or_insert(next)
+        };
+        let mut cover = Cover {
+            primary,
+            secondary: 0,
+            options: Vec::new(),
+            lines: Vec::new(),
+        };
+        for (node, &index) in nodes.iter().enumerate() {
+            let pos = board.pos(index);
+            // the lines out of this node, with their item and crossing item
+            let mut edges = Vec::new();
+            for direction in Direction::iter() {
+                let next = direction.apply_offset(pos);
+                if board.symbol(next).is_none_or(|s| s == Symbol::Empty) {
+                    continue;
+                }
+                let (store, inner) = direction.store(pos);
+                let store_index = board.index(store);
+                // only the option of the node storing a diagonal covers its
+                // crossing, the options of both its ends would clash
+                let crossing = match (inner, store == pos) {
+                    (DirectionInner::DownRight, true) => Some(item((store_index, None))),
+                    (DirectionInner::DownLeft, true) => direction
+                        .may_conflict(pos)
+                        .filter(|&(conflict, _)| board.in_bounds(conflict))
+                        .map(|(conflict, _)| item((board.index(conflict), None))),
+                    _ => None,
+                };
+                let fixed = board.lines[store_index][inner as usize];
+                edges.push((
+                    item((store_index, Some(inner))),
+                    store_index,
+                    inner,
+                    crossing,
+                    fixed,
+                ));
+            }
+            let (colors, lines, even) = match board.board[index].0 {
+                Symbol::Node(color) => (vec![color], 2, false),
+                Symbol::End(color) => (vec![color], 1, false),
+                Symbol::White(n) => {
+                    let allowed = board.allowed[index];
+                    let colors = board
+                        .colors()
+                        .iter()
+                        .copied()
+                        .filter(|&c| allowed.contains(c))
+                        .collect();
+                    (colors, 2 * usize::from(n), true)
+                }
+                Symbol::Empty => unreachable!(),
+            };
+            let mut assigned = Vec::with_capacity(edges.len());
+            let mut each = |assigned: &[Option<Color>]| {
+                let mut option = vec![(node + 1, 0)];
+                let mut drawn = Vec::new();
+                for (&(edge, store_index, inner, crossing, _), &color) in edges.iter().zip(assigned)
+                {
+                    match color {
+                        Some(color) => {
+                            option.push((edge, color.index() as i32 + 1));
+                            option.extend(crossing.map(|crossing| (crossing, 0)));
+                            if store_index == index {
+                                drawn.push((store_index, inner, color));
+                            }
+                        }
+                        None => option.push((edge, UNUSED)),
+                    }
+                }
+                cover.options.push(option);
+                cover.lines.push(drawn);
+            };
+            let fixed = edges.iter().map(|edge| edge.4).collect::<Vec<_>>();
+            assign(&fixed, &colors, lines, even, &mut assigned, &mut each);
+        }
+        cover.secondary = items.len();
+        cover
+    }
+}
+
+// call `each` with every way to give `lines` of the edges of a node one of
+// `colors`, each color an even number of times with `even`, keeping the
+// colors of the lines already drawn in `fixed`
+fn assign(
+    fixed: &[Option<Color>],
+    colors: &[Color],
+    lines: usize,
+    even: bool,
+    assigned: &mut Vec<Option<Color>>,
+    each: &mut dyn FnMut(&[Option<Color>]),
+) {
+    let used = assigned.iter().flatten().count();
+    let Some(&fixed_color) = fixed.get(assigned.len()) else {
+        let balanced = || {
+            colors
+                .iter()
+                .all(|&c| assigned.iter().filter(|&&a| a == Some(c)).count() % 2 == 0)
+        };
+        if used == lines && (!even || balanced()) {
+            each(assigned);
+        }
+        return;
+    };
+    if used + fixed.len() - assigned.len() < lines {
+        return;
+    }
+    let choices = match fixed_color {
+        Some(color) if colors.contains(&color) => vec![Some(color)],
+        Some(_) => return,
+        None if used < lines => std::iter::once(None)
+            .chain(colors.iter().copied().map(Some))
+            .collect(),
+        None => vec![None],
+    };
+    for choice in choices {
+        assigned.push(choice);
+        assign(fixed, colors, lines, even, assigned, each);
+        assigned.pop();
+    }
+}
+
+// draw the lines of a cover on `board` as paths from the first endpoint of
+// each color, or from the end of its fixed lines
+//
+// return whether they make a solution, every color being a single path
+pub(crate) fn draw(board: &mut Board, lines: &[(usize, DirectionInner, Color)]) -> bool {
+    for &color in &board.colors.clone() {
+        let fixed_head = board.result[..board.frozen]
+            .iter()
+            .rev()
+            .find(|line| line.2 == color)
+            .map(|&(pos, direction, _)| direction.apply_offset(pos));
+        let start = match fixed_head {
+            Some(head) => head,
+            None => {
+                let Some(start) = (0..board.board.len())
+                    .filter(|&i| board.board[i].0 == Symbol::color_end(color))
+                    .min_by_key(|&i| node_order(board.pos(i)))
+                else {
+                    continue;
+                };
+                board.board[start].1 += 1;
+                board.pos(start)
+            }
+        };
+        // the lines of the color left to draw, by the nodes at their ends
+        let mut left: HashMap<Point, Vec<Point>> = HashMap::new();
+        for &(store_index, inner, _) in lines.iter().filter(|line| line.2 == color) {
+            if board.lines[store_index][inner as usize].is_some() {
+                continue;
+            }
+            let from = board.pos(store_index);
+            let to = Direction::from(inner).apply_offset(from);
+            left.entry(from).or_default().push(to);
+            left.entry(to).or_default().push(from);
+        }
+        // Hierholzer's algorithm, a single path through every line as the
+        // degrees of the nodes allow no other
+        let mut stack = vec![start];
+        let mut path = Vec::new();
+        while let Some(&point) = stack.last() {
+            match left.get_mut(&point).and_then(Vec::pop) {
+                Some(next) => {
+                    let back = left.get_mut(&next).unwrap();
+                    let i = back.iter().position(|&p| p == point).unwrap();
+                    back.swap_remove(i);
+                    stack.push(next);
+                }
+                None => path.extend(stack.pop()),
+            }
+        }
+        if left.values().any(|ends| !ends.is_empty()) {
+            // a loop apart from the path
+            return false;
+        }
+        for (&from, &to) in path.iter().rev().zip(path.iter().rev().skip(1)) {
+            let offset = (to.0 - from.0, to.1 - from.1);
+            let direction = Direction::iter().find(|d| d.offset() == offset).unwrap();
+            if board.add_line(from, direction, color).is_err() {
+                return false;
+            }
+        }
+    }
+    board.is_solved()
+}
+
+// search the first solution of `board` as an exact cover problem, leaving it
+// on the board
+//
+// every option tried counts as a line attempt in the stats, so the timeout
+// and `max_attempts` bound this search like the backtracking one
+pub(crate) fn solve_dlx(board: &mut Board) -> bool {
+    let cover = Cover::new(board);
+    info!(
+        "exact cover of {} nodes, {} lines and crossings, {} options",
+        cover.primary,
+        cover.secondary,
+        cover.options.len()
+    );
+    let mut links = Links::new(cover.primary, cover.secondary, &cover.options);
+    let cancel = [AtomicBool::new(false)];
+    let timeout = board.options.timeout;
+    let mut start = board.clone();
+    start.stats = Stats::default();
+    let mut solved = None;
+    let (_, timed_out) = with_timeout(timeout, &cancel, || {
+        links.search(
+            &mut Vec::new(),
+            &mut || {
+                board.stats.add_line_calls += 1;
+                cancel[0].load(Ordering::Relaxed) || cancelled(board) || out_of_budget(board)
+            },
+            &mut |chosen| {
+                let mut drawn = start.clone();
+                let lines = chosen
+                    .iter()
+                    .flat_map(|&row| cover.lines[row].iter().copied())
+                    .collect::<Vec<_>>();
+                if draw(&mut drawn, &lines) {
+                    solved = Some(drawn);
+                    true
+                } else {
+                    debug!("cover with a loop apart from its path");
+                    false
+                }
+            },
+        )
+    });
+    match solved {
+        Some(drawn) => {
+            let mut stats = std::mem::take(&mut board.stats);
+            stats.merge(&drawn.stats);
+            *board = drawn;
+            board.stats = stats;
+            true
+        }
+        None => {
+            board.stats.timed_out |= timed_out;
+            false
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use strum::IntoEnumIterator;
+
+use crate::{Board, Color, Direction, Point, Symbol, ValidationError};
+
+// A reason found without searching why a puzzle has no solution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Obstacle {
+    // a problem `Board::validate` reports
+    Invalid(ValidationError),
+    // a white node with fewer lines that can be drawn to it than its passes
+    // need, counting only neighbors a color allowed through it can come from
+    WhiteShort {
+        pos: Point,
+        needed: usize,
+        available: usize,
+    },
+    // a node of `color`, or its other endpoint, that no path drawn from its
+    // endpoint at `from` can reach
+    Disconnected {
+        color: Color,
+        from: Point,
+        pos: Point,
+    },
+}
+
+impl std::fmt::Display for Obstacle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Obstacle::Invalid(e) => write!(f, "{}", e),
+            Obstacle::WhiteShort {
+                pos,
+                needed,
+                available,
+            } => write!(
+                f,
+                "white node {:?} needs {} lines but only {} can be drawn to it",
+                pos, needed, available
+            ),
+            Obstacle::Disconnected { color, from, pos } => write!(
+                f,
+                "{} node {:?} cannot be reached from the {} endpoint {:?}",
+                color, pos, color, from
+            ),
+        }
+    }
+}
+
+impl Board {
+    // the strongest reason found without searching why the puzzle has no
+    // solution, from the checks of `validate` to the lines each white node
+    // can get and the nodes each path can reach, all on the board without
+    // any line drawn
+    //
+    // None does not mean a solution exists, only that these checks found
+    // nothing and the search has to tell
+    pub fn explain(&self) -> Option<Obstacle> {
+        if let Some(problem) = self.validate().into_iter().next() {
+            return Some(Obstacle::Invalid(problem));
+        }
+        let mut bare = Board::new(self.width, self.symbols().collect());
+        bare.allowed = self.allowed.clone();
+
+        for index in 0..bare.board.len() {
+            let pos = bare.pos(index);
+            let Symbol::White(n) = bare.board[index].0 else {
+                continue;
+            };
+            let available = Direction::iter()
+                .filter(|&direction| bare.line_achievable(pos, direction))
+                .count();
+            let needed = 2 * usize::from(n);
+            if available < needed {
+                return Some(Obstacle::WhiteShort {
+                    pos,
+                    needed,
+                    available,
+                });
+            }
+        }
+
+        for &color in bare.colors() {
+            let Some(index) = bare.symbols().position(|s| s == Symbol::color_end(color)) else {
+                continue;
+            };
+            let from = bare.pos(index);
+            if let Some(pos) = bare.unreachable_node(from, color) {
+                return Some(Obstacle::Disconnected { color, from, pos });
+            }
+        }
+        None
+    }
+
+    // the first node of `color`, in reading order, that no path of `color`
+    // drawn from the endpoint `from` on this board can reach
+    fn unreachable_node(&self, from: Point, color: Color) -> Option<Point> {
+        let mut visited = vec![false; self.board.len()];
+        let mut stack = vec![from];
+        visited[self.index(from)] = true;
+        while let Some(point) = stack.pop() {
+            if point != from && self.symbol(point) == Some(Symbol::color_end(color)) {
+                // paths stop at the endpoints
+                continue;
+            }
+            for direction in Direction::iter() {
+                if !self.can_add_line(point, direction, color) {
+                    continue;
+                }
+                let next_point = direction.apply_offset(point);
+                let next_index = self.index(next_point);
+                if !visited[next_index] {
+                    visited[next_index] = true;
+                    stack.push(next_point);
+                }
+            }
+        }
+        (0..self.board.len())
+            .find(|&index| {
+                let symbol = self.board[index].0;
+                !visited[index]
+                    && (symbol == Symbol::color(color) || symbol == Symbol::color_end(color))
+            })
+            .map(|index| self.pos(index))
+    }
+}
@@ -0,0 +1,319 @@
+use log::{debug, error};
+use std::collections::HashSet;
+use strum::IntoEnumIterator;
+
+use crate::rng::Rng;
+use crate::{
+    Board, Color, ColorSet, Direction, DirectionInner, Point, Solution, SolveOptions, Symbol,
+    Uniqueness,
+};
+
+// What kind of board `Board::generate` draws
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    pub width: usize,
+    pub height: usize,
+    // number of colors, at most 26
+    pub colors: usize,
+    // number of white nodes
+    pub whites: usize,
+    // the same seed gives the same board
+    pub seed: u64,
+    // only keep boards with a single solution
+    pub unique: bool,
+    // times the colors allowed through a white node of a board with several
+    // solutions are restricted to make it unique, before drawing another
+    pub perturbations: usize,
+    // only keep boards taking at least this many `add_line` attempts to
+    // solve without pruning
+    pub min_effort: u64,
+    // boards drawn before giving up
+    pub attempts: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions {
+            width: 5,
+            height: 5,
+            colors: 3,
+            whites: 2,
+            seed: 0,
+            unique: false,
+            perturbations: 8,
+            min_effort: 0,
+            attempts: 10_000,
+        }
+    }
+}
+
+// What a cell of a board being drawn holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Free,
+    // a white node and the number of times it is passed so far
+    White(u8),
+    // a node on the path of a color, whether it ends the path
+    Path(Color, bool),
+}
+
+// The paths drawn so far on a board being generated
+#[derive(Clone)]
+struct Sketch {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    // the lines drawn, as stored by `Direction::store`
+    lines: HashSet<(Point, DirectionInner)>,
+}
+
+impl Sketch {
+    fn index(&self, (x, y): Point) -> usize {
+        y as usize * self.width + x as usize
+    }
+
+    fn in_bounds(&self, (x, y): Point) -> bool {
+        x >= 0 && x < self.width as i32 && y >= 0 && y < self.height as i32
+    }
+
+    // whether a line of the path being drawn may go from `pos` in `direction`,
+    // `path` being the nodes it visited so far
+    fn can_draw(&self, pos: Point, direction: Direction, path: &[Point]) -> bool {
+        let next = direction.apply_offset(pos);
+        if !self.in_bounds(next) || self.lines.contains(&direction.store(pos)) {
+            return false;
+        }
+        if let Some(conflict) = direction.may_conflict(pos) {
+            if self.lines.contains(&conflict) {
+                return false;
+            }
+        }
+        match self.cells[self.index(next)] {
+            // the text format has no white node passed more than 4 times
+            Cell::White(n) => n < 4,
+            Cell::Free => !path.contains(&next),
+            Cell::Path(..) => false,
+        }
+    }
+
+    // draw a random path of `color` between two free nodes, passing through
+    // white nodes on the way
+    //
+    // return whether a path could be drawn
+    fn draw_path(&mut self, color: Color, length: usize, rng: &mut Rng) -> bool {
+        let free = (0..self.cells.len())
+            .filter(|&i| self.cells[i] == Cell::Free)
+            .collect::<Vec<_>>();
+        if free.len() < 2 {
+            return false;
+        }
+        let start = free[rng.below(free.len())];
+        let mut path = vec![((start % self.width) as i32, (start / self.width) as i32)];
+        let mut drawn = self.clone();
+        while path.len() < length {
+            let pos = *path.last().unwrap();
+            let mut directions = Direction::iter().collect::<Vec<_>>();
+            rng.shuffle(&mut directions);
+            let Some(direction) = directions
+                .into_iter()
+                .find(|&d| drawn.can_draw(pos, d, &path))
+            else {
+                break;
+            };
+            drawn.lines.insert(direction.store(pos));
+            path.push(direction.apply_offset(pos));
+        }
+
+        // a path ends on a node of its color
+        while path
+            .last()
+            .is_some_and(|&p| matches!(self.cells[self.index(p)], Cell::White(_)))
+        {
+            let end = path.pop().unwrap();
+            let previous = *path.last().unwrap();
+            let direction = Direction::from_offset((end.0 - previous.0, end.1 - previous.1));
+            drawn.lines.remove(&direction.unwrap().store(previous));
+        }
+        if path.len() < 2 {
+            return false;
+        }
+
+        for (i, &pos) in path.iter().enumerate() {
+            let index = self.index(pos);
+            drawn.cells[index] = match drawn.cells[index] {
+                Cell::White(n) => Cell::White(n + 1),
+                _ => Cell::Path(color, i == 0 || i == path.len() - 1),
+            };
+        }
+        *self = drawn;
+        true
+    }
+
+    // the nodes of the sketch, or None if a white node is never passed
+    fn symbols(&self) -> Option<Vec<Symbol>> {
+        self.cells
+            .iter()
+            .map(|&cell| match cell {
+                Cell::Free => Some(Symbol::Empty),
+                Cell::White(0) => None,
+                Cell::White(n) => Some(Symbol::White(n)),
+                Cell::Path(color, true) => Some(Symbol::color_end(color)),
+                Cell::Path(color, false) => Some(Symbol::color(color)),
+            })
+            .collect()
+    }
+}
+
+impl Board {
+    // draw a random board that can be solved
+    //
+    // the paths of the colors are drawn first, without crossing each other,
+    // and the nodes are derived from them, white nodes counting how many
+    // paths pass through them, so the board is solvable by construction.
+    // Each board is still solved as a check, and boards not matching
+    // `unique` or `min_effort` are drawn again
+    //
+    // return None if no board fits within `options.attempts`
+    pub fn generate(options: &GenerateOptions) -> Option<Board> {
+        let cells = options.width * options.height;
+        if cells == 0 || options.colors == 0 || options.colors > Color::iter().count() {
+            return None;
+        }
+        let mut rng = Rng(options.seed);
+        for attempt in 0..options.attempts {
+            let Some(mut board) = draw_board(options, &mut rng) else {
+                continue;
+            };
+            debug!("attempt {} draws a board", attempt + 1);
+
+            let mut check = board.clone();
+            check.set_options(checked());
+            let solvable = if options.unique {
+                let uniqueness;
+                (board, uniqueness) = make_unique(board, options.perturbations);
+                match uniqueness {
+                    Uniqueness::NoSolution => false,
+                    Uniqueness::Unique(_) => true,
+                    Uniqueness::Multiple(..) => continue,
+                }
+            } else {
+                check.solve().is_some()
+            };
+            if !check.validate().is_valid() || !solvable {
+                error!("generated a board the solver cannot solve:\n{}", board);
+                continue;
+            }
+            if options.min_effort > 0 {
+                let mut check = board.clone();
+                check.set_options(SolveOptions {
+                    prune: false,
+                    threads: 1,
+                    ..SolveOptions::default()
+                });
+                check.solve();
+                if check.stats().add_line_calls < options.min_effort {
+                    continue;
+                }
+            }
+            return Some(board);
+        }
+        debug!(
+            "no board among {} attempts for {} cells",
+            options.attempts, cells
+        );
+        None
+    }
+}
+
+// the options generated boards are checked with
+fn checked() -> SolveOptions {
+    SolveOptions {
+        threads: 1,
+        ..SolveOptions::default()
+    }
+}
+
+// search whether `board` has a single solution, and while it has several,
+// restrict the colors allowed through one of its white nodes so that one of
+// the solutions found stays valid and the other does not, at most `budget`
+// times
+//
+// return the last board and whether it has a single solution
+fn make_unique(mut board: Board, budget: usize) -> (Board, Uniqueness) {
+    let mut perturbations = 0;
+    loop {
+        let mut check = board.clone();
+        check.set_options(checked());
+        let uniqueness = check.uniqueness();
+        let Uniqueness::Multiple(first, second) = &uniqueness else {
+            return (board, uniqueness);
+        };
+        let restricted =
+            restrict(&board, first, second).or_else(|| restrict(&board, second, first));
+        match restricted {
+            Some(restricted) if perturbations < budget => {
+                debug!("restrict a white node of a board with several solutions");
+                board = restricted;
+                perturbations += 1;
+            }
+            _ => return (board, uniqueness),
+        }
+    }
+}
+
+// restrict the first white node that `other` passes with a color `solution`
+// does not to the colors of `solution`, or None if there is no such node
+fn restrict(board: &Board, solution: &Solution, other: &Solution) -> Option<Board> {
+    let colors_at = |solution: &Solution, pos: Point| {
+        let mut colors = ColorSet::empty();
+        for &(from, direction, color) in solution.segments() {
+            if from == pos || direction.apply_offset(from) == pos {
+                colors.insert(color);
+            }
+        }
+        colors
+    };
+    let pos = (0..board.height() as i32)
+        .flat_map(|y| (0..board.width() as i32).map(move |x| (x, y)))
+        .filter(|&pos| matches!(board.symbol(pos), Some(Symbol::White(_))))
+        .find(|&pos| {
+            let (kept, dropped) = (colors_at(solution, pos), colors_at(other, pos));
+            Color::iter().any(|c| dropped.contains(c) && !kept.contains(c))
+        })?;
+    let mut board = board.clone();
+    let index = board.index(pos);
+    board.allowed[index] = colors_at(solution, pos);
+    Some(board)
+}
+
+// draw the paths of one board, or None if they do not fit
+fn draw_board(options: &GenerateOptions, rng: &mut Rng) -> Option<Board> {
+    let cells = options.width * options.height;
+    if options.whites + 2 * options.colors > cells {
+        return None;
+    }
+    let mut sketch = Sketch {
+        width: options.width,
+        height: options.height,
+        cells: vec![Cell::Free; cells],
+        lines: HashSet::new(),
+    };
+    let mut order = (0..cells).collect::<Vec<_>>();
+    rng.shuffle(&mut order);
+    for &index in &order[..options.whites] {
+        sketch.cells[index] = Cell::White(0);
+    }
+
+    for (i, color) in Color::iter().take(options.colors).enumerate() {
+        // share the free nodes left between the remaining colors
+        let free = sketch.cells.iter().filter(|&&c| c == Cell::Free).count();
+        let share = free / (options.colors - i);
+        let length = share.max(2) + options.whites;
+        if !(0..10).any(|_| sketch.draw_path(color, length, rng)) {
+            return None;
+        }
+    }
+
+    let symbols = sketch.symbols()?;
+    Some(Board::new(options.width, symbols))
+}
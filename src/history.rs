@@ -0,0 +1,111 @@
+use crate::{Board, Color, Direction, LineError, Point};
+
+// The lines on a board at some point, to come back to with
+// `Board::rollback`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    lines: usize,
+    hash: u64,
+}
+
+impl Board {
+    // take back the last line drawn, which `redo` draws again until another
+    // line is added
+    //
+    // return the line, or None if there is none or it was fixed by
+    // `fix_lines`
+    pub fn undo(&mut self) -> Option<(Point, Direction, Color)> {
+        let &line @ (pos, direction, _) = self.result.last()?;
+        self.remove_line(pos, direction).ok()?;
+        self.undone.push(line);
+        Some(line)
+    }
+
+    // draw again the last line taken back by `undo`
+    //
+    // return None, keeping the line to redo, if there is none or it can no
+    // longer be drawn
+    pub fn redo(&mut self) -> Option<(Point, Direction, Color)> {
+        let line @ (pos, direction, color) = *self.undone.last()?;
+        // `add_line` forgets the lines to redo
+        let mut undone = std::mem::take(&mut self.undone);
+        let added = self.add_line(pos, direction, color);
+        if added.is_ok() {
+            undone.pop();
+        }
+        self.undone = undone;
+        added.ok().map(|_| line)
+    }
+
+    // the lines on the board now, for `rollback`
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            lines: self.result.len(),
+            hash: self.hash,
+        }
+    }
+
+    // take back the lines drawn since `checkpoint`, which `redo` draws again
+    // one by one
+    //
+    // a checkpoint whose lines are no longer all on the board, or one taken
+    // before lines were fixed, leaves the board unchanged
+    pub fn rollback(&mut self, checkpoint: Checkpoint) -> Result<(), LineError> {
+        if checkpoint.lines < self.frozen {
+            return Err(LineError::Fixed);
+        }
+        let Some(count) = self.result.len().checked_sub(checkpoint.lines) else {
+            return Err(LineError::NotFound);
+        };
+        for _ in 0..count {
+            self.undo();
+        }
+        if self.hash != checkpoint.hash {
+            // other lines were drawn in place of those of the checkpoint
+            for _ in 0..count {
+                self.redo();
+            }
+            return Err(LineError::NotFound);
+        }
+        Ok(())
+    }
+
+    // the lines `redo` would draw, the next one last
+    pub fn undone_lines(&self) -> &[(Point, Direction, Color)] {
+        &self.undone
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Board, Color, ColorSet, Direction};
+
+    #[test]
+    fn redo_forgets_lines_once_colors_are_restricted() {
+        let mut board = Board::parse("RrR\nbbb\nB1B").unwrap();
+        let line = ((0, 0), Direction::Right, Color::Red);
+        board.add_line(line.0, line.1, line.2).unwrap();
+        assert_eq!(board.undo(), Some(line));
+        board.set_allowed((1, 0), ColorSet::empty()).unwrap();
+        assert!(board.undone_lines().is_empty());
+        assert_eq!(board.redo(), None);
+        assert!(board.export_lines().is_empty());
+    }
+
+    #[test]
+    fn redo_keeps_a_line_it_cannot_draw() {
+        let mut board = Board::parse("RrR\nbbb\nB1B").unwrap();
+        let line = ((0, 0), Direction::Right, Color::Red);
+        board.add_line(line.0, line.1, line.2).unwrap();
+        board.undo();
+        // restricted without going through `set_allowed`
+        board.allowed[1] = ColorSet::empty();
+        assert_eq!(board.redo(), None);
+        assert_eq!(board.undone_lines(), [line]);
+        assert!(board.export_lines().is_empty());
+
+        board.allowed[1] = ColorSet::ALL;
+        assert_eq!(board.redo(), Some(line));
+        assert!(board.undone_lines().is_empty());
+    }
+}
@@ -0,0 +1,616 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::parse::{json_segments, parse_node};
+use crate::stats::format_duration;
+use crate::{
+    Board, Color, ColorSet, Difficulty, Direction, ParseError, Point, Solution, Stats, Symbol,
+};
+
+// Minimal JSON value, enough to exchange boards and solutions
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    // keys keep their insertion order
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<K: Into<String>>(entries: impl IntoIterator<Item = (K, Json)>) -> Self {
+        Json::Object(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    pub fn point((x, y): Point) -> Self {
+        Json::Array(vec![Json::Number(x as f64), Json::Number(y as f64)])
+    }
+
+    // `[{"color": "Red", "from": [x, y], "to": [x, y]}, ..]`
+    pub fn segments(segments: &[(Point, Direction, Color)]) -> Self {
+        Json::Array(
+            segments
+                .iter()
+                .map(|&(pos, direction, color)| {
+                    Json::object([
+                        ("color", color.to_string().into()),
+                        ("from", Json::point(pos)),
+                        ("to", Json::point(direction.apply_offset(pos))),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    // `{"secs": 0, "nanos": 12345678, "text": "12.346 ms"}`, the whole
+    // seconds and the nanoseconds left as `Duration` holds them
+    pub fn duration(duration: Duration) -> Self {
+        Json::object([
+            ("secs", duration.as_secs().into()),
+            ("nanos", u64::from(duration.subsec_nanos()).into()),
+            ("text", format_duration(duration).into()),
+        ])
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(n: usize) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(n: u64) -> Self {
+        Json::Number(n as f64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(n: f64) -> Self {
+        Json::Number(n)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl Symbol {
+    // `"R"`, `"g"`, `"."` or `"2"`, as in the text format
+    pub fn to_json(self) -> Json {
+        self.to_string().into()
+    }
+
+    // read a node written by `to_json`
+    pub fn from_json(json: &Json) -> Result<Symbol, ParseError> {
+        let invalid = ParseError::InvalidJson { offset: 0 };
+        let Json::String(text) = json else {
+            return Err(invalid);
+        };
+        match parse_node(text) {
+            Some((symbol, allowed)) if allowed == ColorSet::ALL => Ok(symbol),
+            _ => Err(invalid),
+        }
+    }
+}
+
+impl Board {
+    // `{"width": .., "height": .., "symbols": ["R", "2", ..]}` with the nodes
+    // in reading order, white nodes followed by the colors allowed through
+    // them when they are restricted, e.g. `"2[rb]"`
+    pub fn to_json(&self) -> Json {
+        Json::object([
+            ("width", self.width.into()),
+            ("height", self.height.into()),
+            (
+                "symbols",
+                Json::Array(
+                    (0..self.board.len())
+                        .map(|index| self.node_text(index).into())
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+
+    // `to_json` with the lines on the board under `"lines"`, in the order
+    // they were drawn, and the number of them fixed by `fix_lines` under
+    // `"fixed"`, to store a game or a search in progress
+    pub fn state_to_json(&self) -> Json {
+        let mut state = self.to_json();
+        if let Json::Object(entries) = &mut state {
+            entries.push(("lines".into(), Json::segments(&self.result)));
+            entries.push(("fixed".into(), self.frozen.into()));
+        }
+        state
+    }
+
+    // read a board written by `to_json` or `state_to_json`, drawing its
+    // lines again, without its search options and statistics
+    pub fn from_json(json: &Json) -> Result<Board, ParseError> {
+        let invalid = || ParseError::InvalidJson { offset: 0 };
+        let width = match json.get("width") {
+            Some(&Json::Number(width)) if width >= 1.0 && width.fract() == 0.0 => width as usize,
+            _ => return Err(invalid()),
+        };
+        let Some(Json::Array(symbols)) = json.get("symbols") else {
+            return Err(invalid());
+        };
+        let nodes = symbols
+            .iter()
+            .map(|symbol| match symbol {
+                Json::String(text) => parse_node(text).ok_or_else(invalid),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if nodes.is_empty() || !nodes.len().is_multiple_of(width) {
+            return Err(invalid());
+        }
+        let height = nodes.len() / width;
+        if json
+            .get("height")
+            .is_some_and(|h| *h != Json::Number(height as f64))
+        {
+            return Err(invalid());
+        }
+
+        let (symbols, allowed): (Vec<_>, Vec<_>) = nodes.into_iter().unzip();
+        let mut board = Board::new(width, symbols);
+        board.allowed = allowed;
+        if let Some(lines) = json.get("lines") {
+            let lines = json_segments(lines)?;
+            let fixed = match json.get("fixed") {
+                None => 0,
+                Some(&Json::Number(n)) if n.fract() == 0.0 && n as usize <= lines.len() => {
+                    n as usize
+                }
+                _ => return Err(invalid()),
+            };
+            board.fix_lines(&lines[..fixed]).map_err(|_| invalid())?;
+            board.import_lines(&lines[fixed..]).map_err(|_| invalid())?;
+        }
+        Ok(board)
+    }
+}
+
+impl Solution {
+    // `[{"color": "Red", "from": [x, y], "to": [x, y]}, ..]` in the order the
+    // lines were drawn
+    pub fn to_json(&self) -> Json {
+        Json::segments(self.segments())
+    }
+
+    // read a solution written by `to_json`, or the `"segments"` of a JSON
+    // document with the segments fixed before solving under `"given"`
+    pub fn from_json(json: &Json) -> Result<Solution, ParseError> {
+        let segments = json_segments(json)?;
+        let given = match json.get("given") {
+            Some(given) => json_segments(given)?,
+            None => Vec::new(),
+        };
+        if !segments.starts_with(&given) {
+            return Err(ParseError::InvalidJson { offset: 0 });
+        }
+        Ok(Solution::new(segments, given.len()))
+    }
+
+    // `{"Red": [[x, y], ..], ..}`, the nodes visited by each color from one
+    // endpoint to the other
+    pub fn paths_to_json(&self) -> Json {
+        Json::object(self.paths().into_iter().map(|(color, path)| {
+            (
+                color.to_string(),
+                Json::Array(path.into_iter().map(Json::point).collect()),
+            )
+        }))
+    }
+}
+
+impl Stats {
+    // the counters by name, with the time of each color searched in
+    // milliseconds under `color_time_ms`, and the lines of the deepest
+    // state under `deepest` when the search timed out
+    pub fn to_json(&self) -> Json {
+        let mut stats = Json::object([
+            ("add_line_calls", self.add_line_calls.into()),
+            ("lines_placed", self.lines_placed.into()),
+            ("backtracks", self.backtracks.into()),
+            ("max_depth", self.max_depth.into()),
+            ("color_checks", self.color_checks.into()),
+            ("white_checks", self.white_checks.into()),
+            ("transposition_hits", self.transposition_hits.into()),
+            (
+                "color_time_ms",
+                Json::object(
+                    Color::iter()
+                        .map(|c| (c.to_string(), self.color_time[c.index()]))
+                        .filter(|(_, time)| !time.is_zero())
+                        .map(|(c, time)| (c, (time.as_secs_f64() * 1000.0).into())),
+                ),
+            ),
+            ("timed_out", self.timed_out.into()),
+            ("out_of_budget", self.out_of_budget.into()),
+            ("cancelled", self.cancelled.into()),
+        ]);
+        if let (true, Json::Object(entries)) = (self.timed_out, &mut stats) {
+            entries.push(("deepest".into(), Json::segments(&self.deepest)));
+        }
+        stats
+    }
+}
+
+impl Difficulty {
+    // the measures by name, with the rating as a lowercase string
+    pub fn to_json(&self) -> Json {
+        let mut entries = vec![
+            ("attempts", self.attempts.into()),
+            ("backtracks", self.backtracks.into()),
+            ("max_depth", self.max_depth.into()),
+            ("branching", self.branching.into()),
+            (
+                "path_lengths",
+                Json::object(
+                    self.path_lengths
+                        .iter()
+                        .map(|&(color, length)| (color.to_string(), length.into())),
+                ),
+            ),
+            ("whites", self.whites.into()),
+            ("white_passes", u64::from(self.white_passes).into()),
+        ];
+        if let Some(solutions) = self.solutions {
+            entries.push(("solutions", solutions.into()));
+        }
+        entries.push(("score", self.score.into()));
+        entries.push(("rating", self.rating.to_string().into()));
+        Json::object(entries)
+    }
+}
+
+impl Json {
+    // parse a JSON document, failing with the byte offset of the first
+    // character that does not fit, or of the array or object nested deeper
+    // than `MAX_DEPTH`
+    pub fn parse(text: &str) -> Result<Json, usize> {
+        let mut parser = Parser {
+            text,
+            offset: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset < text.len() {
+            return Err(parser.offset);
+        }
+        Ok(value)
+    }
+
+    // the value of `key` in an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+// Arrays and objects `Json::parse` reads inside each other, each one a call
+// deeper on the stack
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+    // arrays and objects open around the value being read
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.offset..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), usize> {
+        if self.text[self.offset..].starts_with(expected) {
+            self.offset += expected.len();
+            Ok(())
+        } else {
+            Err(self.offset)
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, usize> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.nested(Parser::array),
+            Some('{') => self.nested(Parser::object),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.offset;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+                {
+                    self.offset += 1;
+                }
+                self.text[start..self.offset]
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| start)
+            }
+            _ => Err(self.offset),
+        }
+    }
+
+    // read an array or object with `parse`, one level deeper
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json, usize>) -> Result<Json, usize> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.offset);
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn array(&mut self) -> Result<Json, usize> {
+        self.offset += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.offset += 1;
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.offset += 1,
+                Some(']') => {
+                    self.offset += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.offset),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, usize> {
+        self.offset += 1;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.offset += 1;
+            return Ok(Json::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.offset += 1,
+                Some('}') => {
+                    self.offset += 1;
+                    return Ok(Json::Object(entries));
+                }
+                _ => return Err(self.offset),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, usize> {
+        self.expect("\"")?;
+        let mut s = String::new();
+        loop {
+            let c = self.peek().ok_or(self.offset)?;
+            self.offset += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escaped = self.peek().ok_or(self.offset)?;
+                    self.offset += 1;
+                    s.push(match escaped {
+                        '"' => '"',
+                        '\\' => '\\',
+                        '/' => '/',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => self.unicode_escape()?,
+                        _ => return Err(self.offset - 1),
+                    });
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    // the character of a `\uXXXX` escape, the `\u` read, joining a high
+    // surrogate with the low surrogate escaped right after it, as in
+    // `\uD83D\uDE00`
+    fn unicode_escape(&mut self) -> Result<char, usize> {
+        let start = self.offset - 2;
+        let code = self.hex4()?;
+        let code = match code {
+            0xd800..=0xdbff => {
+                let low_start = self.offset;
+                self.expect("\\u")?;
+                let low = self.hex4()?;
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return Err(low_start);
+                }
+                0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00)
+            }
+            // a low surrogate without a high one before it
+            0xdc00..=0xdfff => return Err(start),
+            code => code,
+        };
+        char::from_u32(code).ok_or(start)
+    }
+
+    // the four hexadecimal digits of a `\u` escape
+    fn hex4(&mut self) -> Result<u32, usize> {
+        let hex = self
+            .text
+            .get(self.offset..self.offset + 4)
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or(self.offset)?;
+        let code = u32::from_str_radix(hex, 16).unwrap();
+        self.offset += 4;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solution_json_replays_through_add_line() {
+        let mut solved = Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap();
+        let solution = solved.solve().unwrap();
+        let text = Json::object([
+            ("board", solved.to_json()),
+            ("segments", solution.to_json()),
+        ])
+        .to_string();
+
+        let json = Json::parse(&text).unwrap();
+        let mut board = Board::from_json(json.get("board").unwrap()).unwrap();
+        let read = Solution::from_json(json.get("segments").unwrap()).unwrap();
+        assert_eq!(read, solution);
+        for &(pos, direction, color) in read.segments() {
+            assert_eq!(board.add_line(pos, direction, color), Ok(()));
+        }
+        assert!(board.is_solved());
+    }
+
+    #[test]
+    fn board_json_keeps_the_restricted_colors() {
+        let board = Board::parse("R2[rb]R\nB1B").unwrap();
+        let text = board.to_json().to_string();
+        let read = Board::from_json(&Json::parse(&text).unwrap()).unwrap();
+        assert_eq!(read.to_string(), board.to_string());
+        assert_eq!(read.to_json(), board.to_json());
+    }
+
+    #[test]
+    fn state_json_keeps_the_lines_and_the_fixed_ones() {
+        let mut board = Board::parse("RrrR").unwrap();
+        board
+            .fix_lines(&[((0, 0), Direction::Right, Color::Red)])
+            .unwrap();
+        board
+            .add_line((1, 0), Direction::Right, Color::Red)
+            .unwrap();
+        let text = board.state_to_json().to_string();
+        let read = Board::from_json(&Json::parse(&text).unwrap()).unwrap();
+        assert_eq!(read.export_lines(), board.export_lines());
+        assert_eq!(read.fixed_lines(), board.fixed_lines());
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error_not_a_stack_overflow() {
+        let deep = "[".repeat(200_000);
+        assert_eq!(Json::parse(&deep), Err(MAX_DEPTH));
+        let objects = "{\"a\":".repeat(MAX_DEPTH + 1);
+        assert!(Json::parse(&objects).is_err());
+
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn surrogate_pairs_join_into_one_character() {
+        let text = r#""smile \uD83D\uDE00, \u00e9""#;
+        assert_eq!(
+            Json::parse(text),
+            Ok(Json::String("smile \u{1F600}, \u{e9}".into()))
+        );
+        // a surrogate out of its pair
+        assert_eq!(Json::parse(r#""\uD83D""#), Err(7));
+        assert_eq!(Json::parse(r#""\uD83Dx""#), Err(7));
+        assert_eq!(Json::parse(r#""\uD83D\u0041""#), Err(7));
+        assert_eq!(Json::parse(r#""\uDE00""#), Err(1));
+        assert_eq!(Json::parse(r#""\u+041""#), Err(3));
+    }
+}
@@ -0,0 +1,113 @@
+mod alphabet;
+mod backend;
+mod board;
+mod cancel;
+mod catalog;
+mod clock;
+mod difficulty;
+mod direction;
+mod dlx;
+mod explain;
+mod generate;
+mod history;
+mod json;
+mod observer;
+mod parallel;
+mod parse;
+mod progress;
+mod prune;
+#[cfg(feature = "python")]
+mod python;
+mod render;
+mod restart;
+mod rng;
+#[cfg(feature = "sat")]
+mod sat;
+mod snapshot;
+mod solution;
+mod solver;
+mod stats;
+mod svg;
+mod symbol;
+mod symmetry;
+mod validate;
+mod verify;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use alphabet::Alphabet;
+pub use backend::{cross_check, Disagreement, Solver};
+pub use board::{Board, IllegalMove, LineError, StateError};
+pub use cancel::{CancellationToken, Cancelled};
+pub use catalog::Level;
+pub use difficulty::{Difficulty, RateError, Rating};
+pub use direction::{Direction, DirectionInner};
+pub use explain::Obstacle;
+pub use generate::GenerateOptions;
+pub use history::Checkpoint;
+pub use json::Json;
+pub use observer::SearchObserver;
+pub use parse::{parse_segments, ParseError};
+pub use progress::{Progress, ProgressObserver};
+pub use snapshot::{ResumeError, SearchSnapshot};
+pub use solution::Solution;
+pub use solver::{Algorithm, DirectionOrder, SolveOptions, Uniqueness};
+pub use stats::{format_duration, Stats};
+pub use symbol::{Color, ColorSet, Symbol};
+pub use validate::{solve, PuzzleError, ValidationError, ValidationReport};
+pub use verify::{PartialCheck, VerifyError};
+#[cfg(feature = "wasm")]
+pub use wasm::{solve_js, solve_json};
+
+pub type Point = (i32, i32);
+
+// Tie-break rule for every choice the search makes: nodes are taken in
+// reading order (by row, then by column), then directions and colors in
+// their declaration order. Heuristics must only reorder candidates with a
+// stable sort over this order so equal scores keep the same output.
+pub fn node_order((x, y): Point) -> (i32, i32) {
+    (y, x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 3x3 board with a single solution, its white node passed by both colors
+    const BOARD: &str = "Rr.\n.2B\nRbB";
+
+    #[test]
+    fn parse_reads_a_3x3_board() {
+        let board = Board::parse(BOARD).unwrap();
+        assert_eq!((board.width(), board.height()), (3, 3));
+        assert_eq!(board.symbol((1, 1)), Some(Symbol::White(2)));
+        assert_eq!(board.symbol((2, 2)), Some(Symbol::End(Color::Blue)));
+        assert_eq!(board.colors(), [Color::Red, Color::Blue]);
+    }
+
+    #[test]
+    fn solve_lists_the_segments_of_each_color() {
+        let solution = Board::parse(BOARD).unwrap().solve().unwrap();
+        assert_eq!(
+            solution.segments(),
+            [
+                ((0, 0), Direction::Right, Color::Red),
+                ((1, 0), Direction::Down, Color::Red),
+                ((1, 1), Direction::DownLeft, Color::Red),
+                ((2, 1), Direction::Left, Color::Blue),
+                ((1, 1), Direction::Down, Color::Blue),
+                ((1, 2), Direction::Right, Color::Blue),
+            ]
+        );
+        assert_eq!(solution.segments_for(Color::Blue).count(), 3);
+        assert_eq!(solution.segments_for(Color::Green).count(), 0);
+    }
+
+    #[test]
+    fn solve_reports_bad_boards_as_errors() {
+        assert!(matches!(solve("Rr!"), Err(PuzzleError::Parse(_))));
+        assert!(matches!(solve("Rr."), Err(PuzzleError::Invalid(_))));
+        assert_eq!(solve("RbR\nBrB").unwrap(), None);
+        assert!(solve(BOARD).unwrap().is_some());
+    }
+}
@@ -1,455 +1,1579 @@
 use iter_tools::Itertools;
-use log::{debug, error, info, trace, warn};
-use std::io;
-use strum::IntoEnumIterator;
-use strum_macros::{Display, EnumIter};
+use log::{error, info, warn};
+use lyne::{
+    format_duration, parse_segments, Algorithm, Alphabet, Board, Color, Direction, DirectionOrder,
+    Disagreement, Json, ParseError, Point, ProgressObserver, SearchSnapshot, Solution,
+    SolveOptions, Solver, Symbol, Uniqueness,
+};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-type Point = (i32, i32);
+mod checkpoint;
+mod commands;
+mod visualize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
-pub enum Color {
-    Red,
-    Green,
-    Blue,
+use checkpoint::Checkpointer;
+use visualize::{Pace, Visualizer};
+
+// How a solution is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    // the nodes visited by each color, in order, followed by the board drawn
+    // with its lines in colors when `grid` is set
+    Paths {
+        grid: bool,
+    },
+    // the lines grouped by color
+    Segments,
+    // the board drawn with its lines
+    Grid {
+        ansi: bool,
+    },
+    // an SVG image of the board and its lines
+    Svg {
+        cell_size: u32,
+    },
+    // a JSON document with the board and its lines
+    Json,
+    // the screen points to drag through for each color to play the
+    // solution, from the pixel position of the node (0,0) and the distance
+    // between nodes, with the pause between drags
+    Drag {
+        origin: Point,
+        spacing: u32,
+        delay: Option<u64>,
+        json: bool,
+    },
 }
 
-impl Color {
-    pub fn next(self) -> Option<Self> {
-        match self {
-            Color::Red => Some(Color::Green),
-            Color::Green => Some(Color::Blue),
-            Color::Blue => None,
+impl Format {
+    // whether the output, and the errors with it, are JSON
+    fn is_json(self) -> bool {
+        matches!(self, Format::Json | Format::Drag { json: true, .. })
+    }
+}
+
+// the JSON document printed for `--format json`, with the search statistics
+// for `--stats`
+fn json_result(board: &Board, solution: Option<&Solution>, stats: Option<&Json>) -> Json {
+    let mut entries = vec![
+        ("solved", solution.is_some().into()),
+        ("timed_out", board.stats().timed_out.into()),
+        ("board", board.to_json()),
+    ];
+    if let Some(solution) = solution {
+        entries.push(("segments", solution.to_json()));
+        entries.push(("paths", solution.paths_to_json()));
+        if !solution.given().is_empty() {
+            entries.push(("given", Json::segments(solution.given())));
         }
     }
+    if let Some(stats) = stats {
+        entries.push(("stats", stats.clone()));
+    }
+    Json::object(entries)
+}
+
+// report an error on stderr, as a JSON document for `--format json`
+fn report_error(json: bool, message: impl std::fmt::Display) {
+    if json {
+        eprintln!("{}", Json::object([("error", message.to_string().into())]));
+    } else {
+        error!("{}", message);
+    }
+}
+
+// report an error in the arguments on stderr, with where to find the usage
+fn report_usage_error(message: &str) {
+    error!("{}", message);
+    eprintln!("{}", USAGE_HINT);
+}
+
+// report each of `errors` on stderr
+fn report_errors(json: bool, errors: Vec<String>) {
+    for e in errors {
+        report_error(json, e);
+    }
 }
 
-// Puzzle nodes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Symbol {
-    R,
-    G,
-    B,
-    REnd,
-    GEnd,
-    BEnd,
-    Empty,
-    White(u8),
+fn write_paths(out: &mut dyn Write, paths: Vec<(Color, Vec<Point>)>) -> io::Result<()> {
+    for (color, path) in paths {
+        let nodes = path.iter().map(|(x, y)| format!("({},{})", x, y));
+        writeln!(out, "{}: {}", color, nodes.format(" -> "))?;
+    }
+    Ok(())
 }
 
-impl From<char> for Symbol {
-    fn from(c: char) -> Self {
-        match c {
-            'r' => Symbol::R,
-            'g' => Symbol::G,
-            'b' => Symbol::B,
-            'R' => Symbol::REnd,
-            'G' => Symbol::GEnd,
-            'B' => Symbol::BEnd,
-            '.' => Symbol::Empty,
-            '1' => Symbol::White(1),
-            '2' => Symbol::White(2),
-            '3' => Symbol::White(3),
-            '4' => Symbol::White(4),
-            _ => panic!("invalid symbol: {}", c),
+fn write_segments(out: &mut dyn Write, segments: &[(Point, Direction, Color)]) -> io::Result<()> {
+    for (color, group) in &segments.iter().group_by(|s| s.2) {
+        writeln!(out, "{}:", color)?;
+        for (point, direction, _) in group {
+            writeln!(out, "{} {:?}", direction, point)?;
         }
     }
+    Ok(())
 }
 
-impl Symbol {
-    pub fn color(c: Color) -> Self {
-        match c {
-            Color::Red => Symbol::R,
-            Color::Green => Symbol::G,
-            Color::Blue => Symbol::B,
+// print one drag per color through the pixel positions of its nodes, only
+// for the lines not given beforehand
+fn write_drags(
+    out: &mut dyn Write,
+    solution: &Solution,
+    (origin, spacing, delay): (Point, u32, Option<u64>),
+    json: bool,
+) -> io::Result<()> {
+    let paths = match solution.given() {
+        [] => solution.paths(),
+        _ => solution.added_paths(),
+    };
+    let drags = paths.into_iter().map(|(color, path)| {
+        let points = path
+            .into_iter()
+            .map(|(x, y)| {
+                let spacing = spacing as i32;
+                (origin.0 + x * spacing, origin.1 + y * spacing)
+            })
+            .collect::<Vec<_>>();
+        (color, points)
+    });
+    if json {
+        let mut entries = Vec::new();
+        if let Some(delay) = delay {
+            entries.push(("delay_ms", delay.into()));
         }
+        let drags = drags.map(|(color, points)| {
+            Json::object([
+                ("color", color.to_string().into()),
+                (
+                    "points",
+                    Json::Array(points.into_iter().map(Json::point).collect()),
+                ),
+            ])
+        });
+        entries.push(("drags", Json::Array(drags.collect())));
+        return writeln!(out, "{}", Json::object(entries));
     }
+    if let Some(delay) = delay {
+        writeln!(out, "delay {}", delay)?;
+    }
+    for (color, points) in drags {
+        let points = points.iter().map(|(x, y)| format!("{},{}", x, y));
+        writeln!(out, "{}: {}", color, points.format(" "))?;
+    }
+    Ok(())
+}
 
-    pub fn color_end(c: Color) -> Self {
-        match c {
-            Color::Red => Symbol::REnd,
-            Color::Green => Symbol::GEnd,
-            Color::Blue => Symbol::BEnd,
+// print a solution
+fn write_result(
+    out: &mut dyn Write,
+    board: &Board,
+    solution: &Solution,
+    format: Format,
+    stats: Option<&Json>,
+) -> io::Result<()> {
+    // a hint shows the fixed segments apart from the next ones
+    let given = !solution.given().is_empty();
+    match format {
+        Format::Paths { .. } if given => {
+            writeln!(out, "Given:")?;
+            write_paths(out, solution.given_paths())?;
+            writeln!(out, "Next:")?;
+            write_paths(out, solution.added_paths())?;
         }
+        Format::Paths { .. } => write_paths(out, solution.paths())?,
+        Format::Segments if given => {
+            writeln!(out, "Given:")?;
+            write_segments(out, solution.given())?;
+            writeln!(out, "Next:")?;
+            write_segments(out, solution.added())?;
+        }
+        Format::Segments => write_segments(out, solution.segments())?,
+        Format::Grid { ansi: false } => write!(out, "{}", solution.render(board))?,
+        Format::Grid { ansi: true } => write!(out, "{}", solution.render_ansi(board))?,
+        Format::Svg { cell_size } => write!(out, "{}", solution.to_svg(board, cell_size))?,
+        Format::Json => writeln!(out, "{}", json_result(board, Some(solution), stats))?,
+        Format::Drag {
+            origin,
+            spacing,
+            delay,
+            json,
+        } => write_drags(out, solution, (origin, spacing, delay), json)?,
+    }
+    if let Format::Paths { grid: true } = format {
+        write!(out, "\n{}", solution.render_ansi(board))?;
     }
+    Ok(())
 }
 
-// Only store these 4 directions
-// The other 4 are just the reverse of these
+fn write_solutions(
+    out: &mut dyn Write,
+    board: &Board,
+    solutions: &[Solution],
+    mode: Mode,
+    format: Format,
+    stats: Option<&Json>,
+) -> io::Result<()> {
+    let timed_out = board.stats().timed_out;
+    match mode {
+        Mode::First => match (solutions, format) {
+            // the puzzle itself is still worth an image
+            ([], Format::Svg { cell_size }) => write!(out, "{}", board.to_svg(cell_size))?,
+            ([], Format::Json) => writeln!(out, "{}", json_result(board, None, stats))?,
+            ([], Format::Drag { json: true, .. }) => {
+                writeln!(out, "{}", json_result(board, None, stats))?
+            }
+            ([], _) if timed_out => writeln!(out, "timed out")?,
+            ([], _) => writeln!(out, "no solution")?,
+            _ => {
+                for solution in solutions {
+                    write_result(out, board, solution, format, stats)?;
+                }
+            }
+        },
+        Mode::All => {
+            for (i, solution) in solutions.iter().enumerate() {
+                writeln!(out, "Solution {}:", i + 1)?;
+                write_result(out, board, solution, format, stats)?;
+            }
+            if timed_out {
+                writeln!(out, "timed out")?;
+            }
+        }
+        Mode::Count if timed_out => writeln!(out, "{} solutions, timed out", solutions.len())?,
+        Mode::Count => writeln!(out, "{} solutions", solutions.len())?,
+        Mode::Unique => match solutions {
+            _ if timed_out => {
+                writeln!(out, "timed out")?;
+                write_solutions(out, board, solutions, Mode::All, format, stats)?;
+            }
+            [] => writeln!(out, "no solution")?,
+            [solution] => {
+                writeln!(out, "unique")?;
+                write_result(out, board, solution, format, stats)?;
+            }
+            _ => {
+                writeln!(out, "multiple solutions")?;
+                write_solutions(out, board, solutions, Mode::All, format, stats)?;
+            }
+        },
+    }
+    out.flush()
+}
+
+// the whole search ran and proved there is no solution
+const EXIT_NO_SOLUTION: i32 = 1;
+// bad arguments, unreadable or invalid puzzle, or failure to write the result
+const EXIT_INVALID_INPUT: i32 = 2;
+// the search gave up on `--timeout` or `--max-nodes`, or was stopped before
+// it could tell, as when quitting `lyne tui` or `lyne play`
+const EXIT_TIMEOUT: i32 = 3;
+// `--unique` found several solutions
+const EXIT_MULTIPLE_SOLUTIONS: i32 = 4;
+// `--cross-check` found the two searches disagreeing
+const EXIT_DISAGREEMENT: i32 = 5;
+
+// frames per second of `--visualize` without a pace
+const DEFAULT_FPS: f64 = 30.0;
+
+const ORDERING_ERROR: &str = "--ordering needs one of fixed, greedy, target, random[:SEED]";
+
+// printed after an error in the arguments instead of the whole `USAGE`
+const USAGE_HINT: &str = "see lyne --help for the usage";
+
+const USAGE: &str = "\
+Usage: lyne [solve] [OPTIONS] [FILE]
+       lyne verify <PUZZLE> <SOLUTION> [--format json] [--symbols FILE] [--pad]
+       lyne verify-unique [OPTIONS] [FILE]
+       lyne generate <WIDTHxHEIGHT> [OPTIONS]
+       lyne rate [--timeout <DURATION>] [--format json] [FILE]
+       lyne solve-all <DIR> [OPTIONS]
+       lyne bench [--runs N] [OPTIONS] [FILE]
+       lyne tui [OPTIONS] [FILE]
+       lyne hint [OPTIONS] [FILE]
+       lyne check [OPTIONS] [FILE]
+       lyne play [OPTIONS] [FILE]
+
+Solve the LYNE puzzle read from FILE, or from stdin when FILE is absent or `-`.
+Puzzles separated by blank lines are solved one after the other, each named
+by a `# name` line above it if any. Segments listed after a `---` line below
+a puzzle are kept and the rest is completed.
+verify checks that the segments in SOLUTION solve PUZZLE.
+verify-unique prints UNIQUE, MULTIPLE or UNSOLVABLE for the puzzle.
+solve-all solves every .txt file under DIR into a .solution file next to it.
+bench solves each puzzle N times [default: 10] and prints the times taken.
+hint prints the next segment to draw after the segments given below the puzzle
+or with --partial, as `Red Right (0, 0)`.
+check tells whether the given segments can still be completed, and otherwise
+names the first of them after which no solution is left.
+play lets you solve the puzzle by hand with commands like `r right 0,0`, read
+from stdin, or from the terminal when the puzzle is read from stdin.
+tui follows the search move by move on the terminal, taking commands typed
+there: Enter for the next move, c to run, i to inspect the lines, q to quit.
+
+Options:
+  --format <FORMAT>       text, segments, svg or json [default: text]
+  --render                draw the solution on the board instead of listing
+                          its paths
+  --no-render             only list the paths, which are followed by the
+                          board drawn in colors on a terminal
+  --cell-size <PX>        pixels between nodes for svg [default: 40]
+  --autoplay              print the screen points to drag through for each
+                          color, as text or with --format json
+  --origin <X,Y>          pixel position of the node (0,0) for --autoplay
+  --spacing <PX>          pixels between nodes for --autoplay
+  --delay <MS>            pause between drags to print with --autoplay
+  --partial <FILE>        keep the segments in FILE and complete the rest
+  --pad                   fill the rows shorter than the longest one with
+                          empty cells instead of rejecting them
+  --symbols <FILE>        read the nodes with the characters of the [symbols]
+                          table of a TOML file, e.g. red = \"t\"
+  --verify <FILE>         check that the segments in FILE solve the puzzle
+  --rate                  rate the difficulty of the puzzle
+  --explain               tell why there is no solution when a check before
+                          the search can show it
+  --cross-check [NAME]    solve again with dfs, dlx, sat or count (the search
+                          for every solution) and report any disagreement
+                          [default: dlx, or dfs with --algorithm dlx]
+  --progress [force]      report the progress of the search on stderr every
+                          second, only on a terminal unless forced
+  --visualize [step|FPS]  draw the search on the terminal, one move per Enter
+                          or at most FPS frames per second [default: 30],
+                          also --watch
+  --step                  pause after each line drawn or taken back until
+                          Enter, the same as --visualize step
+  --checkpoint <FILE>     save the search in FILE every minute and when it
+                          gives up, removing FILE once the search ends
+  --resume <FILE>         go on with the search saved in FILE by --checkpoint
+  --output <FILE>         write the result to FILE, `{name}` is the puzzle name
+  --svg <FILE>            also draw the solution as an SVG image in FILE
+  --all                   print every distinct solution
+  --count                 print the number of distinct solutions, searching
+                          the whole tree, also --count-solutions
+  --max-solutions <N>     stop after N solutions
+  --unique                check that there is exactly one solution
+  --color-order <COLORS>  solve these colors first, e.g. bgr [default: fewest nodes first]
+  --ordering <ORDER>      order of the moves tried: fixed, greedy, target
+                          (toward the endpoint) or random[:SEED] [default: fixed]
+  --restarts <N>          start the search over in a new random order after N
+                          line attempts, doubling N each time
+  --algorithm <NAME>      dfs (depth first search), dlx (dancing links over an
+                          exact cover problem) or sat (a SAT solver, with the
+                          sat feature) [default: dfs]
+  --threads <N>           threads searching for the first solution [default: all cores]
+  --no-prune              search without pruning hopeless paths
+  --tt-size <N>           dead end states remembered by the search, 0 for none
+                          [default: 1048576]
+  --timeout <DURATION>    give up searching after DURATION, in seconds or
+                          with a unit, e.g. 500ms or 2m
+  --max-nodes <N>         give up searching after N line attempts, on a
+                          single thread, as if it timed out
+  --stats                 report the work done by the search
+  --quiet                 print nothing but the solution, leaving the verdict
+                          of --unique to the exit code
+  --verbose               report the time spent solving on stderr
+  --help                  print this message
+
+Generate options:
+  --colors <N>            number of colors, 1 to 26 [default: 3]
+  --whites <N>            number of white nodes [default: 2]
+  --seed <N>              draw the same board again [default: random]
+  --unique                only print a board with a single solution
+  --min-effort <N>        only print a board taking at least N line attempts
+                          to solve without pruning
+  --count <N>             print N boards drawn with the seeds following
+                          each other, separated by blank lines [default: 1]
+
+Exit codes: 0 solved, 1 proven to have no solution, 2 invalid input, 3 timed
+out or stopped before the end, 4 several solutions for --unique, 5 disagreeing searches for --cross-check.
+With --verify: 0 valid, 1 invalid. check: 0 can be completed, 1 cannot.
+generate exits with 1 if no board fits the options";
+
+// The search `--cross-check` compares the solver with
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DirectionInner {
-    Right = 0,
-    DownRight = 1,
-    Down = 2,
-    DownLeft = 3,
+enum Checker {
+    Algorithm(Algorithm),
+    // the search for every solution, stopped at the first one
+    Count,
 }
 
-impl From<u8> for DirectionInner {
-    fn from(d: u8) -> Self {
-        match d {
-            0 => DirectionInner::Right,
-            1 => DirectionInner::DownRight,
-            2 => DirectionInner::Down,
-            3 => DirectionInner::DownLeft,
-            _ => panic!("invalid direction: {}", d),
+impl Checker {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dfs" => Some(Checker::Algorithm(Algorithm::Dfs)),
+            "dlx" => Some(Checker::Algorithm(Algorithm::Dlx)),
+            #[cfg(feature = "sat")]
+            "sat" => Some(Checker::Algorithm(Algorithm::Sat)),
+            "count" => Some(Checker::Count),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Checker::Algorithm(Algorithm::Dfs) => "dfs",
+            Checker::Algorithm(Algorithm::Dlx) => "dlx",
+            #[cfg(feature = "sat")]
+            Checker::Algorithm(Algorithm::Sat) => "sat",
+            Checker::Count => "count",
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumIter)]
-pub enum Direction {
-    Right,
-    DownRight,
-    Down,
-    DownLeft,
-    Left,
-    UpLeft,
-    Up,
-    UpRight,
+// What to search and print
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    First,
+    All,
+    Count,
+    Unique,
 }
 
-impl From<DirectionInner> for Direction {
-    fn from(d: DirectionInner) -> Self {
-        match d {
-            DirectionInner::Right => Direction::Right,
-            DirectionInner::DownRight => Direction::DownRight,
-            DirectionInner::Down => Direction::Down,
-            DirectionInner::DownLeft => Direction::DownLeft,
+// Command line options
+#[derive(Debug, Clone)]
+struct Args {
+    // read from stdin when absent
+    file: Option<String>,
+    // segments drawn beforehand, as after a `---` line in the input
+    partial: Option<String>,
+    // characters the puzzles use for the nodes instead of the usual ones
+    symbols: Alphabet,
+    // fill short rows with empty cells
+    pad: bool,
+    // a proposed solution to check instead of solving
+    verify: Option<String>,
+    rate: bool,
+    // tell why there is no solution
+    explain: bool,
+    // solve the puzzle again with this search and compare
+    cross_check: Option<Checker>,
+    // draw the search as it goes
+    visualize: Option<Pace>,
+    // report the progress of the search every second
+    progress: bool,
+    // save the search in this file every minute and when it gives up
+    checkpoint: Option<String>,
+    // go on with the search saved in this file
+    resume: Option<String>,
+    output: Option<String>,
+    // also draw the result as an SVG image in this file
+    svg: Option<String>,
+    cell_size: u32,
+    mode: Mode,
+    max_solutions: Option<usize>,
+    format: Format,
+    options: SolveOptions,
+    stats: bool,
+    quiet: bool,
+    verbose: bool,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut args = args.peekable();
+        let mut file = None;
+        let mut partial = None;
+        let mut symbols = Alphabet::default();
+        let mut pad = false;
+        let mut verify = None;
+        let mut rate = false;
+        let mut explain = false;
+        let mut checkpoint = None;
+        let mut resume = None;
+        let mut cross_check = None;
+        let mut visualize = None;
+        let mut progress = false;
+        let mut output = None;
+        let mut svg = None;
+        let mut mode = Mode::First;
+        let mut max_solutions = None;
+        let mut render = None;
+        let mut format_name = String::from("text");
+        let mut cell_size = 40;
+        let mut autoplay = false;
+        let mut origin = None;
+        let mut spacing = None;
+        let mut delay = None;
+        let mut options = SolveOptions::default();
+        let mut stats = false;
+        let mut quiet = false;
+        let mut verbose = false;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                "--partial" => partial = Some(args.next().ok_or("--partial needs a file path")?),
+                "--symbols" => {
+                    symbols = read_alphabet(&args.next().ok_or("--symbols needs a file path")?)?
+                }
+                "--pad" => pad = true,
+                "--verify" => verify = Some(args.next().ok_or("--verify needs a file path")?),
+                "--rate" => rate = true,
+                "--explain" => explain = true,
+                "--cross-check" => {
+                    #[cfg(not(feature = "sat"))]
+                    if args.peek().is_some_and(|name| name == "sat") {
+                        return Err(
+                            "--cross-check sat needs lyne built with the sat feature".into()
+                        );
+                    }
+                    let checker = args.peek().and_then(|name| Checker::parse(name));
+                    if checker.is_some() {
+                        args.next();
+                    }
+                    cross_check = Some(checker);
+                }
+                "--progress" => {
+                    // progress lines would only clutter a log
+                    progress =
+                        args.next_if(|a| a == "force").is_some() || io::stderr().is_terminal();
+                }
+                "--visualize" | "--watch" => {
+                    visualize = Some(match args.peek().map(|a| (a.as_str(), a.parse::<f64>())) {
+                        Some(("step", _)) => Pace::Step,
+                        Some((_, Ok(fps))) if fps > 0.0 && fps.is_finite() => Pace::Fps(fps),
+                        Some((_, Ok(_))) => {
+                            return Err(format!("{} needs a positive number of frames", arg))
+                        }
+                        _ => {
+                            visualize = Some(Pace::Fps(DEFAULT_FPS));
+                            continue;
+                        }
+                    });
+                    args.next();
+                }
+                "--step" => visualize = Some(Pace::Step),
+                "--checkpoint" => {
+                    checkpoint = Some(args.next().ok_or("--checkpoint needs a file path")?)
+                }
+                "--resume" => resume = Some(args.next().ok_or("--resume needs a file path")?),
+                "--output" => output = Some(args.next().ok_or("--output needs a file path")?),
+                "--svg" => svg = Some(args.next().ok_or("--svg needs a file path")?),
+                "--all" => mode = Mode::All,
+                "--count" | "--count-solutions" => mode = Mode::Count,
+                "--unique" => mode = Mode::Unique,
+                "--render" => render = Some(true),
+                "--no-render" => render = Some(false),
+                "--color-order" => {
+                    let order = args.next().ok_or("--color-order needs color letters")?;
+                    let colors = order
+                        .chars()
+                        .map(|c| {
+                            Symbol::try_from(c)
+                                .ok()
+                                .filter(|s| !s.is_end())
+                                .and_then(|s| s.node_color())
+                                .ok_or(format!("invalid color {:?} in --color-order", c))
+                        })
+                        .collect::<Result<_, _>>()?;
+                    options.color_order = Some(colors);
+                }
+                "--ordering" => {
+                    options.direction_order = match args.next().as_deref() {
+                        Some("fixed") => DirectionOrder::Fixed,
+                        Some("greedy") => DirectionOrder::Greedy,
+                        Some("target") => DirectionOrder::Target,
+                        Some("random") => DirectionOrder::Random(0),
+                        Some(order) => match order.strip_prefix("random:").map(str::parse) {
+                            Some(Ok(seed)) => DirectionOrder::Random(seed),
+                            _ => return Err(ORDERING_ERROR.into()),
+                        },
+                        None => return Err(ORDERING_ERROR.into()),
+                    }
+                }
+                "--threads" => match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => options.threads = n,
+                    _ => return Err("--threads needs a positive number".into()),
+                },
+                "--no-prune" => options.prune = false,
+                "--tt-size" => match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) => options.transposition_size = n,
+                    _ => return Err("--tt-size needs a number of states".into()),
+                },
+                "--timeout" => match args.next().as_deref().and_then(parse_duration) {
+                    Some(timeout) => options.timeout = Some(timeout),
+                    _ => return Err("--timeout needs a positive duration, e.g. 30 or 500ms".into()),
+                },
+                "--algorithm" => {
+                    options.algorithm = match args.next().as_deref() {
+                        Some("dfs") => Algorithm::Dfs,
+                        Some("dlx") => Algorithm::Dlx,
+                        #[cfg(feature = "sat")]
+                        Some("sat") => Algorithm::Sat,
+                        #[cfg(not(feature = "sat"))]
+                        Some("sat") => {
+                            return Err(
+                                "--algorithm sat needs lyne built with the sat feature".into()
+                            )
+                        }
+                        _ => return Err("--algorithm needs one of dfs, dlx, sat".into()),
+                    }
+                }
+                "--restarts" => match args.next().map(|n| n.parse::<u64>()) {
+                    Some(Ok(n)) if n > 0 => options.restart_attempts = Some(n),
+                    _ => return Err("--restarts needs a positive number of attempts".into()),
+                },
+                "--max-nodes" => match args.next().map(|n| n.parse::<u64>()) {
+                    Some(Ok(n)) if n > 0 => options.max_attempts = Some(n),
+                    _ => return Err("--max-nodes needs a positive number".into()),
+                },
+                "--stats" => stats = true,
+                "--quiet" => quiet = true,
+                "--verbose" => verbose = true,
+                "--format" => match args.next() {
+                    Some(name) if ["text", "segments", "svg", "json"].contains(&name.as_str()) => {
+                        format_name = name
+                    }
+                    _ => return Err("--format needs one of text, segments, svg, json".into()),
+                },
+                "--cell-size" => match args.next().map(|n| n.parse::<u32>()) {
+                    Some(Ok(n)) if n > 0 => cell_size = n,
+                    _ => return Err("--cell-size needs a positive number".into()),
+                },
+                "--autoplay" => autoplay = true,
+                "--origin" => {
+                    let value = args
+                        .next()
+                        .ok_or("--origin needs a position, e.g. 812,344")?;
+                    origin = Some(
+                        value
+                            .split_once(',')
+                            .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                            .filter(|&(x, y): &Point| x >= 0 && y >= 0)
+                            .ok_or(format!(
+                                "--origin needs a position of non-negative pixels, e.g. 812,344, not {:?}",
+                                value
+                            ))?,
+                    );
+                }
+                "--spacing" => match args.next().map(|n| n.parse::<u32>()) {
+                    Some(Ok(n)) if n > 0 => spacing = Some(n),
+                    _ => return Err("--spacing needs a positive number of pixels".into()),
+                },
+                "--delay" => match args.next().map(|n| n.parse::<u64>()) {
+                    Some(Ok(n)) => delay = Some(n),
+                    _ => return Err("--delay needs a number of milliseconds".into()),
+                },
+                "--max-solutions" => match args.next().map(|n| n.parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => max_solutions = Some(n),
+                    _ => return Err("--max-solutions needs a positive number".into()),
+                },
+                "-" if file.is_none() => file = Some(None),
+                _ if !arg.starts_with('-') && file.is_none() => file = Some(Some(arg)),
+                _ if !arg.starts_with('-') || arg == "-" => {
+                    return Err(format!("a single FILE is read, {:?} is one too many", arg))
+                }
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        if (visualize.is_some() || progress) && mode != Mode::First {
+            return Err("--visualize and --progress only search the first solution".into());
         }
+        if visualize.is_some() && progress {
+            return Err("--visualize and --progress cannot be combined".into());
+        }
+        if checkpoint.is_some() || resume.is_some() {
+            if mode != Mode::First {
+                return Err("--checkpoint and --resume only search the first solution".into());
+            }
+            if visualize.is_some() || progress {
+                return Err(
+                    "--checkpoint and --resume cannot be combined with --visualize and --progress"
+                        .into(),
+                );
+            }
+            if options.algorithm != Algorithm::Dfs || options.restart_attempts.is_some() {
+                return Err("--checkpoint and --resume save the depth first search only".into());
+            }
+        }
+        if ["svg", "json"].contains(&format_name.as_str()) && mode != Mode::First {
+            return Err(format!(
+                "--format {} only prints a single solution",
+                format_name
+            ));
+        }
+        let format = match format_name.as_str() {
+            _ if autoplay => {
+                let (Some(origin), Some(spacing)) = (origin, spacing) else {
+                    return Err("--autoplay needs --origin and --spacing".into());
+                };
+                if !["text", "json"].contains(&format_name.as_str()) {
+                    return Err("--autoplay prints text or json".into());
+                }
+                Format::Drag {
+                    origin,
+                    spacing,
+                    delay,
+                    json: format_name == "json",
+                }
+            }
+            "svg" => Format::Svg { cell_size },
+            "json" => Format::Json,
+            "segments" => Format::Segments,
+            _ if render == Some(true) => Format::Grid {
+                ansi: output.is_none() && io::stdout().is_terminal(),
+            },
+            _ => Format::Paths {
+                grid: render.is_none() && output.is_none() && io::stdout().is_terminal(),
+            },
+        };
+        let file = file.flatten();
+        // `{name}` in the output path is replaced by the name of the puzzle
+        let name = match &file {
+            Some(file) => Path::new(file)
+                .file_stem()
+                .map_or("stdin".into(), |stem| stem.to_string_lossy()),
+            None => "stdin".into(),
+        };
+        let output = output.map(|path| path.replace("{name}", &name));
+        let svg = svg.map(|path| path.replace("{name}", &name));
+        // against dancing links by default, or the depth first search when
+        // dancing links is the solver
+        let cross_check = cross_check.map(|checker| {
+            checker.unwrap_or(match options.algorithm {
+                Algorithm::Dlx => Checker::Algorithm(Algorithm::Dfs),
+                _ => Checker::Algorithm(Algorithm::Dlx),
+            })
+        });
+        Ok(Args {
+            file,
+            partial,
+            symbols,
+            pad,
+            verify,
+            rate,
+            explain,
+            cross_check,
+            visualize,
+            progress,
+            checkpoint,
+            resume,
+            output,
+            svg,
+            cell_size,
+            mode,
+            max_solutions,
+            format,
+            options,
+            stats,
+            quiet,
+            verbose,
+        })
     }
 }
 
-impl Direction {
-    pub fn to_inner(self) -> (DirectionInner, bool) {
-        match self {
-            Direction::Up => (DirectionInner::Down, true),
-            Direction::Down => (DirectionInner::Down, false),
-            Direction::Left => (DirectionInner::Right, true),
-            Direction::Right => (DirectionInner::Right, false),
-            Direction::UpRight => (DirectionInner::DownLeft, true),
-            Direction::UpLeft => (DirectionInner::DownRight, true),
-            Direction::DownRight => (DirectionInner::DownRight, false),
-            Direction::DownLeft => (DirectionInner::DownLeft, false),
-        }
+// a duration in seconds, or with a unit among ms, s, m and h, e.g. `1.5`,
+// `500ms` or `2m`
+fn parse_duration(text: &str) -> Option<Duration> {
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let scale = match unit {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return None,
+    };
+    let seconds = number.parse::<f64>().ok()? * scale;
+    (seconds > 0.0 && seconds.is_finite()).then(|| Duration::from_secs_f64(seconds))
+}
+
+impl Args {
+    // the puzzles of FILE or stdin
+    fn read_input(&self) -> io::Result<String> {
+        read_input(self.file.as_deref())
+    }
+
+    // where the puzzles are read from, for the errors about them
+    fn source(&self) -> String {
+        self.file.clone().unwrap_or_else(|| "stdin".into())
     }
+}
+
+fn read_input(file: Option<&str>) -> io::Result<String> {
+    let mut input = String::new();
+    match file {
+        Some(path) => File::open(path)?.read_to_string(&mut input)?,
+        None => io::stdin().read_to_string(&mut input)?,
+    };
+    Ok(input)
+}
+
+// the characters for the nodes in the TOML file at `path`, for `--symbols`
+fn read_alphabet(path: &str) -> Result<Alphabet, String> {
+    let text = read_input(Some(path)).map_err(|e| format!("failed to read {}: {}", path, e))?;
+    Alphabet::parse(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
+// A puzzle and the solutions searched on it
+struct Solved {
+    board: Board,
+    solutions: Vec<Solution>,
+    elapsed: Duration,
+}
 
-    pub fn store(self, pos: Point) -> (Point, DirectionInner) {
-        let (direction_inner, reverse) = self.to_inner();
-        if reverse {
-            (self.apply_offset(pos), direction_inner)
+impl Solved {
+    fn exit_code(&self, mode: Mode) -> i32 {
+        if self.board.stats().timed_out {
+            EXIT_TIMEOUT
+        } else if self.solutions.is_empty() {
+            EXIT_NO_SOLUTION
+        } else if mode == Mode::Unique && self.solutions.len() > 1 {
+            EXIT_MULTIPLE_SOLUTIONS
         } else {
-            (pos, direction_inner)
+            0
         }
     }
 
-    pub fn offset(self) -> (i32, i32) {
-        match self {
-            Direction::Up => (0, -1),
-            Direction::UpRight => (1, -1),
-            Direction::Right => (1, 0),
-            Direction::DownRight => (1, 1),
-            Direction::Down => (0, 1),
-            Direction::DownLeft => (-1, 1),
-            Direction::Left => (-1, 0),
-            Direction::UpLeft => (-1, -1),
+    // why the puzzle has no solution for `--explain`, when the search proved
+    // there is none
+    fn explanation(&self, args: &Args) -> Option<String> {
+        if !args.explain || self.exit_code(args.mode) != EXIT_NO_SOLUTION {
+            return None;
         }
+        Some(match self.board.explain() {
+            Some(obstacle) => obstacle.to_string(),
+            None if !self.board.fixed_lines().is_empty() => {
+                "the given segments cannot be completed, see lyne check".into()
+            }
+            None => "no reason found before searching, every way to draw the paths fails".into(),
+        })
     }
 
-    pub fn apply_offset(self, (x, y): Point) -> Point {
-        let offset = self.offset();
-        (x + offset.0, y + offset.1)
+    // the outcome in a few words, e.g. `no solution` or `3 solutions`
+    fn status(&self, mode: Mode) -> String {
+        let count = self.solutions.len();
+        match (mode, self.exit_code(mode)) {
+            (Mode::Count, EXIT_TIMEOUT) => format!("{} solutions, timed out", count),
+            (Mode::Count, _) => format!("{} solutions", count),
+            (_, EXIT_TIMEOUT) => "timed out".into(),
+            (_, EXIT_NO_SOLUTION) => "no solution".into(),
+            (_, EXIT_MULTIPLE_SOLUTIONS) => "multiple solutions".into(),
+            (Mode::Unique, _) => "unique".into(),
+            _ => "solved".into(),
+        }
     }
 
-    // Beveled edges cannot cross each other
-    pub fn may_conflict(self, (x, y): Point) -> Option<(Point, DirectionInner)> {
-        match self {
-            Direction::Up => None,
-            Direction::Down => None,
-            Direction::Left => None,
-            Direction::Right => None,
-            Direction::UpRight => Some(((x, y - 1), DirectionInner::DownRight)),
-            Direction::UpLeft => Some(((x, y - 1), DirectionInner::DownLeft)),
-            Direction::DownRight => Some(((x + 1, y), DirectionInner::DownLeft)),
-            Direction::DownLeft => Some(((x - 1, y), DirectionInner::DownRight)),
+    // the statistics for `--stats`, or of a timed out search
+    fn stats_json(&self) -> Json {
+        let mut stats = self.board.stats().to_json();
+        if let Json::Object(entries) = &mut stats {
+            entries.push((
+                "elapsed_ms".into(),
+                (self.elapsed.as_secs_f64() * 1000.0).into(),
+            ));
+            entries.push(("elapsed".into(), Json::duration(self.elapsed)));
+            entries.push(("attempts_per_second".into(), self.attempt_rate().into()));
+        }
+        stats
+    }
+
+    // line attempts of the search per second
+    fn attempt_rate(&self) -> f64 {
+        self.board.stats().add_line_calls as f64 / self.elapsed.as_secs_f64().max(1e-9)
+    }
+
+    // print the statistics on stderr, with where a timed out search got stuck
+    fn report_stats(&self, args: &Args) {
+        let timed_out = self.board.stats().timed_out;
+        // a timed out search shows where it got stuck, unless asked for quiet
+        if args.stats || timed_out && !args.quiet {
+            eprintln!("{}", self.board.stats());
+            eprintln!("elapsed: {}", format_duration(self.elapsed));
+            eprintln!("attempts per second: {:.0}", self.attempt_rate());
+        }
+        if timed_out && !args.quiet {
+            let deepest = &self.board.stats().deepest;
+            eprintln!("deepest state reached, {} lines:", deepest.len());
+            for (point, direction, color) in deepest {
+                eprintln!("{} {} {:?}", color, direction, point);
+            }
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct Board {
-    board: Vec<(Symbol, u8)>, // simluates a 2d array
-    width: usize,
-    height: usize,
-    lines: Vec<[Option<Color>; 4]>, // store the currect state of conneced lines, index by start position of the line
-    result: Vec<(Point, Direction, Color)>,
-}
-
-impl Board {
-    // convert a point to a index
-    #[inline]
-    fn index(&self, (x, y): Point) -> usize {
-        (y * self.width as i32 + x) as usize
-    }
-
-    // convert a index to a point
-    #[inline]
-    fn pos(&self, index: usize) -> Point {
-        ((index % self.width) as i32, (index / self.width) as i32)
-    }
-
-    // add a connected line to the board if it is legal
-    //
-    // return whether the line is legal
-    fn add_line(&mut self, start_pos: Point, direction: Direction, color: Color) -> bool {
-        trace!("try add line ({:?}, {}, {})", start_pos, direction, color);
-        // println!("{:?}", self.board);
-        let offset_pos = direction.apply_offset(start_pos);
-        if offset_pos.0 < 0
-            || offset_pos.0 >= self.width as i32
-            || offset_pos.1 < 0
-            || offset_pos.1 >= self.height as i32
-        {
-            // line is out of bounds
-            return false;
+// the board of a puzzle with its given segments, ready to be solved with the
+// options of `args`
+fn prepare(input: &str, partial: Option<&str>, args: &Args) -> Result<Board, Vec<String>> {
+    let (input, given) = split_given(input, partial, &args.symbols).map_err(|e| vec![e])?;
+    let mut board = parse_puzzle(input, &args.symbols, args.pad)?;
+    if let Some(given) = given {
+        let segments = parse_segments(given).map_err(|e| vec![format!("given {}", e)])?;
+        board
+            .fix_lines(&segments)
+            .map_err(|e| vec![format!("given {}", e)])?;
+    }
+    board.set_options(args.options.clone());
+    Ok(board)
+}
+
+// the puzzle and its given segments, after a `---` line below it or else in
+// `partial`, an error in the arguments if both give them
+//
+// with `-` an empty cell in `symbols`, a `---` line is a row of the puzzle
+// and the given segments can only be in `partial`
+fn split_given<'a>(
+    input: &'a str,
+    partial: Option<&'a str>,
+    symbols: &Alphabet,
+) -> Result<(&'a str, Option<&'a str>), String> {
+    let separator = Some("\n---").filter(|s| symbols.translate(s) == *s);
+    match separator.and_then(|separator| input.split_once(separator)) {
+        Some(_) if partial.is_some() => {
+            Err("--partial cannot be used on a puzzle with given segments after a --- line".into())
         }
+        Some((board, given)) => Ok((board, given.split_once('\n').map(|(_, g)| g))),
+        None => Ok((input, partial)),
+    }
+}
+
+// parse a puzzle written with `symbols`, with its short rows filled with
+// empty cells if `pad`, and check that it can be solved, returning every
+// problem found in the input otherwise
+fn parse_puzzle(input: &str, symbols: &Alphabet, pad: bool) -> Result<Board, Vec<String>> {
+    let board = read_board(input, symbols, pad).map_err(|e| vec![e.to_string()])?;
+    let report = board.validate();
+    if !report.is_valid() {
+        return Err(report.into_iter().map(|e| e.to_string()).collect());
+    }
+    Ok(board)
+}
+
+// parse a puzzle written with `symbols`, with its short rows filled with
+// empty cells if `pad`, without checking it
+fn read_board(input: &str, symbols: &Alphabet, pad: bool) -> Result<Board, ParseError> {
+    let input = symbols.translate(input);
+    if pad {
+        Board::parse_padded(&input)
+    } else {
+        Board::parse(&input)
+    }
+}
+
+// parse, check and solve one puzzle the way the arguments ask, returning
+// every problem found in the input if it cannot be solved
+//
+// segments after a `---` line, or in `partial`, are fixed before solving
+fn solve_one(input: &str, partial: Option<&str>, args: &Args) -> Result<Solved, Vec<String>> {
+    solve_board(prepare(input, partial, args)?, args)
+}
+
+// solve a board ready from `prepare` the way the arguments ask, failing only
+// on the search to `--resume`
+fn solve_board(mut board: Board, args: &Args) -> Result<Solved, Vec<String>> {
+    warn!("start solving");
+
+    let now = Instant::now();
 
-        if let Some((conflict_point, direction_inner)) = direction.may_conflict(start_pos) {
-            if self.lines[self.index(conflict_point)][direction_inner as usize].is_some() {
-                // crossing with the other beveled edge
-                return false;
+    let solutions = match args.mode {
+        Mode::First if args.checkpoint.is_some() || args.resume.is_some() => {
+            let mut checkpointer = Checkpointer::new(args.checkpoint.clone());
+            let solution = match &args.resume {
+                Some(path) => {
+                    let snapshot = read_snapshot(path)?;
+                    board
+                        .resume_observed(&snapshot, &mut checkpointer)
+                        .map_err(|e| vec![format!("cannot resume {}: {}", path, e)])?
+                }
+                None => board.solve_observed(&mut checkpointer),
+            };
+            if !board.stats().timed_out && !board.stats().cancelled {
+                checkpointer.finish();
             }
+            solution.into_iter().collect()
         }
-        let offset_index = self.index(offset_pos);
-        let offset_point = self.board[offset_index];
-        if let Symbol::White(n) = offset_point.0 {
-            if offset_point.1 + 1 > n {
-                // point reach to max number of lines
-                return false;
+        Mode::First => match args.visualize {
+            Some(pace) => {
+                let mut visualizer = Visualizer::new(pace);
+                let solution = board.solve_observed(&mut visualizer);
+                let verdict = match &solution {
+                    Some(_) => "solved",
+                    None if board.stats().timed_out => "timed out",
+                    None => "no solution",
+                };
+                visualizer.finish(&board, verdict);
+                solution.into_iter().collect()
             }
-        } else if offset_point.0 == Symbol::color(color)
-            || offset_point.0 == Symbol::color_end(color)
-        {
-            if offset_point.1 > 0 {
-                // point already connected
-                return false;
+            None if args.progress => {
+                let mut progress =
+                    ProgressObserver::new(Duration::from_secs(1), |p| eprintln!("{}", p));
+                board.solve_observed(&mut progress).into_iter().collect()
             }
-        } else {
-            // color mismatch
-            return false;
-        }
-        let (store_pos, direction_inner) = direction.store(start_pos);
-        let index = self.index(store_pos);
-        let point = self.board[index];
-        if !(point.0 == Symbol::color(color)
-            || point.0 == Symbol::color_end(color)
-            || matches!(point.0, Symbol::White(_)))
-        {
-            // color mismatch
-            return false;
+            None => board.solve().into_iter().collect(),
+        },
+        Mode::All | Mode::Count => board.solve_all(args.max_solutions),
+        Mode::Unique => match board.uniqueness() {
+            Uniqueness::NoSolution => Vec::new(),
+            Uniqueness::Unique(solution) => vec![solution],
+            Uniqueness::Multiple(first, second) => vec![first, second],
+        },
+    };
+    let elapsed = now.elapsed();
+    if solutions.is_empty() {
+        warn!("no solution");
+    }
+    if board.stats().timed_out {
+        warn!("timed out");
+    }
+    Ok(Solved {
+        board,
+        solutions,
+        elapsed,
+    })
+}
+
+// read the search saved by `--checkpoint` in `path`
+fn read_snapshot(path: &str) -> Result<SearchSnapshot, Vec<String>> {
+    let failed = |e: String| vec![format!("failed to read {}: {}", path, e)];
+    let text = read_input(Some(path)).map_err(|e| failed(e.to_string()))?;
+    let json =
+        Json::parse(&text).map_err(|offset| failed(format!("invalid JSON at byte {}", offset)))?;
+    SearchSnapshot::from_json(&json).map_err(|e| failed(e.to_string()))
+}
+
+// solve the puzzle again with `checker` for `--cross-check`, and report
+// whether it agrees with the first solution in `solved` on stderr
+//
+// return the exit code of a disagreement, or None
+fn cross_check_one(
+    input: &str,
+    partial: Option<&str>,
+    args: &Args,
+    solved: &Solved,
+    checker: Checker,
+) -> Option<i32> {
+    let json = args.format.is_json();
+    let solver = Checker::Algorithm(args.options.algorithm).name();
+    if solved.board.stats().timed_out {
+        if !args.quiet {
+            eprintln!("cross-check skipped, {} timed out", solver);
         }
-        let line = self.lines[index].get_mut(direction_inner as usize).unwrap();
-        if line.is_some() {
-            // line already exists
-            return false;
-        } else {
-            *line = Some(color);
-        }
-        self.board[offset_index].1 += 1;
-        self.result.push((start_pos, direction, color));
-        true
-    }
-
-    // remove a connected line from the board
-    fn remove_line(&mut self, start_pos: Point, direction: Direction) -> bool {
-        trace!("remove line ({:?}, {:?})", start_pos, direction);
-        let offset_index = self.index(direction.apply_offset(start_pos));
-        self.board[offset_index].1 -= 1;
-        let (store_pos, direction_inner) = direction.store(start_pos);
-        let store_index = self.index(store_pos);
-        if self.lines[store_index][direction_inner as usize].is_none() {
-            return false;
-        }
-        self.lines[store_index][direction_inner as usize].take();
-        self.result.pop();
-        true
-    }
-}
-
-fn solve_color(board: &mut Board, color: Color) -> bool {
-    let start = board
-        .board
-        .iter()
-        .position(|&s| s.0 == Symbol::color_end(color));
-    if let Some(start_idx) = start {
-        info!("solving color {}", color);
-        debug!("{:?}", board.board);
-        board.board[start_idx].1 += 1;
-        let start = board.pos(start_idx);
-        let res = solve(board, start, color);
-        if !res {
-            // backtrack to previous color
-            info!("backtrack to previous color");
-            board.board[start_idx].1 -= 1;
-        }
-        res
-    } else {
-        info!("no start found for color {}", color);
-        if let Some(next_color) = color.next() {
-            solve_color(board, next_color)
+        return None;
+    }
+    // the board was checked by `solve_one`
+    let board = prepare(input, partial, args).ok()?;
+    let found = solved.solutions.first().cloned();
+    let first = |_: &Board| found.clone();
+    let second = |board: &Board| match checker {
+        Checker::Algorithm(algorithm) => algorithm.solve(board),
+        Checker::Count => board.clone().solve_all(Some(1)).pop(),
+    };
+    let names = [solver, checker.name()];
+    match lyne::cross_check(&board, [&first, &second]) {
+        Ok(()) => {
+            if !args.quiet {
+                eprintln!("cross-check: {} and {} agree", names[0], names[1]);
+            }
+            None
+        }
+        Err(Disagreement::Solvability(i)) => {
+            report_error(
+                json,
+                format!(
+                    "cross-check: {} finds a solution, {} finds none",
+                    names[i],
+                    names[1 - i]
+                ),
+            );
+            Some(EXIT_DISAGREEMENT)
+        }
+        Err(Disagreement::Invalid(i, e)) => {
+            report_error(
+                json,
+                format!("cross-check: the solution of {} is wrong: {}", names[i], e),
+            );
+            Some(EXIT_DISAGREEMENT)
+        }
+    }
+}
+
+// A puzzle of the input, without the `#` lines heading it
+struct Puzzle {
+    // the line of the input its first row is on
+    line: usize,
+    // the text of its first `#` line, e.g. `Level 3` for `# Level 3`
+    name: Option<String>,
+    text: String,
+}
+
+impl Puzzle {
+    // `3`, `3 (Level 3)` with a name, or `3 (Level 3, 5x4)` with a size too,
+    // to follow `Puzzle` in the output
+    fn describe(&self, number: usize, size: Option<(usize, usize)>) -> String {
+        let details = self
+            .name
+            .iter()
+            .cloned()
+            .chain(size.map(|(width, height)| format!("{}x{}", width, height)))
+            .collect::<Vec<_>>();
+        if details.is_empty() {
+            number.to_string()
         } else {
-            white_solved(board)
+            format!("{} ({})", number, details.join(", "))
         }
     }
 }
 
-fn move_to_next_color(board: &mut Board, color: Color) -> bool {
-    if let Some(next_color) = color.next() {
-        info!("move to next color from {} to {}", color, next_color);
-        solve_color(board, next_color)
-    } else {
-        info!("all color connected");
-        white_solved(board)
-    }
-}
-
-fn solve(board: &mut Board, point: (i32, i32), color: Color) -> bool {
-    trace!("solving {:?} at {:?}", color, point);
-    for direction in Direction::iter() {
-        if board.add_line(point, direction, color) {
-            let next_point = direction.apply_offset(point);
-            if board.board[board.index(next_point)].0 == Symbol::color_end(color) {
-                if color_solved(board, color) {
-                    info!("solved color {:?}", color);
-                    if move_to_next_color(board, color) {
-                        return true;
-                    } // else continue to solve this color
-                } else {
-                    trace!("color {:?} reach to end but not all connected", color);
-                }
-            } else {
-                let result = solve(board, next_point, color);
-                if result {
-                    return true;
-                }
+// split the input into puzzles separated by blank lines, with the line
+// number each of them starts at
+//
+// `#` lines heading a puzzle, even apart from it, are taken out of its text
+// and the first of them names it
+fn split_puzzles(input: &str) -> Vec<Puzzle> {
+    let mut puzzles = Vec::new();
+    let mut name = None;
+    let mut current: Option<Puzzle> = None;
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            puzzles.extend(current.take());
+        } else if let Some(puzzle) = &mut current {
+            puzzle.text.push('\n');
+            puzzle.text.push_str(line);
+        } else if let Some(header) = line.trim_start().strip_prefix('#') {
+            if name.is_none() && !header.trim().is_empty() {
+                name = Some(header.trim().to_string());
             }
-            board.remove_line(point, direction);
+        } else {
+            current = Some(Puzzle {
+                line: i + 1,
+                name: name.take(),
+                text: line.to_string(),
+            });
         }
     }
-    false
+    puzzles.extend(current);
+    puzzles
 }
 
-fn color_solved(board: &Board, color: Color) -> bool {
-    let mut board_clone = board.board.iter().map(|s| s.0).collect::<Vec<_>>();
-    for (i, line) in board.lines.iter().enumerate() {
-        for (dir, line_color) in line.iter().enumerate() {
-            if let Some(line_color) = line_color {
-                if *line_color == color {
-                    let direction: Direction = DirectionInner::from(dir as u8).into();
-                    let pos = board.pos(i);
+// the only puzzle of `input` without its header, or None if it holds
+// several
+fn single_puzzle(input: &str) -> Option<String> {
+    match &split_puzzles(input)[..] {
+        [] => Some(input.to_string()),
+        [puzzle] => Some(puzzle.text.clone()),
+        _ => None,
+    }
+}
+
+// the segments of `--partial`, if any
+fn read_partial(args: &Args) -> Result<Option<String>, String> {
+    args.partial
+        .as_deref()
+        .map(|path| read_input(Some(path)).map_err(|e| format!("failed to read {}: {}", path, e)))
+        .transpose()
+}
+
+// the only puzzle read for a subcommand and the segments of `--partial`, or
+// None after reporting why they cannot be read
+fn read_single_puzzle(args: &Args, json: bool) -> Option<(String, Option<String>)> {
+    let name = args.source();
+    let input = match args.read_input() {
+        Ok(input) => input,
+        Err(e) => {
+            report_error(json, format!("failed to read {}: {}", name, e));
+            return None;
+        }
+    };
+    let Some(input) = single_puzzle(&input) else {
+        report_error(json, format!("{} holds several puzzles", name));
+        return None;
+    };
+    let partial = match read_partial(args) {
+        Ok(partial) => partial,
+        Err(e) => {
+            report_error(json, e);
+            return None;
+        }
+    };
+    if let Err(e) = split_given(&input, partial.as_deref(), &args.symbols) {
+        report_usage_error(&e);
+        return None;
+    }
+    Some((input, partial))
+}
+
+// the board of the only puzzle read for a subcommand, with its given segments
+// and the options of `args`, or None after reporting every problem found
+fn prepare_single_puzzle(args: &Args, json: bool) -> Option<Board> {
+    let (input, partial) = read_single_puzzle(args, json)?;
+    prepare(&input, partial.as_deref(), args)
+        .map_err(|errors| report_errors(json, errors))
+        .ok()
+}
 
-                    let i2 = board.index(direction.apply_offset(pos));
-                    board_clone[i] = Symbol::Empty;
-                    board_clone[i2] = Symbol::Empty;
+// the only puzzle read for a subcommand solved the way the arguments ask, or
+// None after reporting every problem found
+fn solve_single_puzzle(args: &Args, json: bool) -> Option<Solved> {
+    let board = prepare_single_puzzle(args, json)?;
+    solve_board(board, args)
+        .map_err(|errors| report_errors(json, errors))
+        .ok()
+}
+
+// solve every puzzle of a batch, each result after a header line and a
+// summary at the end
+//
+// return the exit code of the first puzzle not solved, or 0
+fn solve_batch(puzzles: &[Puzzle], args: &Args) -> i32 {
+    if matches!(args.format, Format::Svg { .. }) || args.format.is_json() {
+        report_error(
+            args.format.is_json(),
+            "--format svg and json only print a single puzzle",
+        );
+        return EXIT_INVALID_INPUT;
+    }
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                report_error(false, format!("failed to create {}: {}", path, e));
+                return EXIT_INVALID_INPUT;
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+
+    let now = Instant::now();
+    let (mut solved_count, mut unsolved_count, mut error_count) = (0, 0, 0);
+    let mut exit_code = 0;
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let number = i + 1;
+        let written = match solve_one(&puzzle.text, None, args) {
+            Err(errors) => {
+                error_count += 1;
+                for e in errors {
+                    report_error(
+                        false,
+                        format!(
+                            "puzzle {} starting at line {}: {}",
+                            puzzle.describe(number, None),
+                            puzzle.line,
+                            e
+                        ),
+                    );
                 }
+                if exit_code == 0 {
+                    exit_code = EXIT_INVALID_INPUT;
+                }
+                writeln!(out, "Puzzle {}: invalid", puzzle.describe(number, None))
             }
+            Ok(solved) => {
+                let code = solved.exit_code(args.mode);
+                if code == 0 {
+                    solved_count += 1;
+                } else {
+                    unsolved_count += 1;
+                    if exit_code == 0 {
+                        exit_code = code;
+                    }
+                }
+                if args.stats || solved.board.stats().timed_out && !args.quiet {
+                    eprintln!("Puzzle {}:", puzzle.describe(number, None));
+                }
+                solved.report_stats(args);
+                if let Some(reason) = solved.explanation(args) {
+                    eprintln!("Puzzle {}: {}", puzzle.describe(number, None), reason);
+                }
+                write_batch_result(&mut out, puzzle, number, &solved, args.mode, args.format)
+            }
+        };
+        if let Err(e) = written {
+            report_error(
+                false,
+                format!(
+                    "failed to write the result to {}: {}",
+                    args.output.as_deref().unwrap_or("stdout"),
+                    e
+                ),
+            );
+            return EXIT_INVALID_INPUT;
         }
     }
 
-    !board_clone.contains(&Symbol::color(color))
+    if !args.quiet {
+        let summary = writeln!(
+            out,
+            "{} puzzles: {} solved, {} unsolved, {} invalid in {}",
+            puzzles.len(),
+            solved_count,
+            unsolved_count,
+            error_count,
+            format_duration(now.elapsed())
+        );
+        if summary.and_then(|_| out.flush()).is_err() {
+            return EXIT_INVALID_INPUT;
+        }
+    }
+    exit_code
 }
 
-fn white_solved(board: &Board) -> bool {
-    for (c, count) in board.board.iter() {
-        if let Symbol::White(n) = c {
-            if n != count {
-                info!("white not solved, backtrack");
-                return false;
-            }
+// print a puzzle of a batch, `Puzzle 3 (5x4): solved` or `Puzzle 3 (Level 3,
+// 5x4): solved` with a name, and its solutions
+fn write_batch_result(
+    out: &mut dyn Write,
+    puzzle: &Puzzle,
+    number: usize,
+    solved: &Solved,
+    mode: Mode,
+    format: Format,
+) -> io::Result<()> {
+    let Solved {
+        board, solutions, ..
+    } = solved;
+    writeln!(
+        out,
+        "Puzzle {}: {}",
+        puzzle.describe(number, Some((board.width(), board.height()))),
+        solved.status(mode)
+    )?;
+    match (mode, &solutions[..]) {
+        (Mode::Count, _) | (_, []) => {}
+        (Mode::First, [solution]) | (Mode::Unique, [solution]) => {
+            write_result(out, board, solution, format, None)?
         }
+        _ => write_solutions(out, board, solutions, Mode::All, format, None)?,
     }
-    true
+    out.flush()
 }
 
 fn main() {
     pretty_env_logger::init();
-    let mut lines = io::stdin().lines();
-    let Some(fist_line) = lines.next() else {
-        error!("no input");
-        return;
+    type Command = fn(std::iter::Skip<std::env::Args>) -> Result<i32, String>;
+    let command = std::env::args().nth(1);
+    let subcommand: Option<Command> = match command.as_deref() {
+        Some("verify") => Some(commands::verify::run),
+        Some("verify-unique") => Some(commands::verify_unique::run),
+        Some("generate") => Some(commands::generate::run),
+        Some("rate") => Some(commands::rate::run),
+        Some("solve-all") => Some(commands::solve_all::run),
+        Some("bench") => Some(commands::bench::run),
+        Some("tui") => Some(commands::tui::run),
+        Some("hint") => Some(commands::hint::run),
+        Some("check") => Some(commands::check::run),
+        Some("play") => Some(commands::play::run),
+        _ => None,
     };
-    let fist_line = fist_line.unwrap();
-    let length = fist_line.len();
-    let mut board = Vec::new();
-    for c in fist_line.chars() {
-        board.push(Symbol::from(c));
+    if let Some(run) = subcommand {
+        let code = run(std::env::args().skip(2)).unwrap_or_else(|e| {
+            report_usage_error(&e);
+            EXIT_INVALID_INPUT
+        });
+        std::process::exit(code);
     }
-    for line in lines {
-        let line = line.unwrap();
-        if line.len() != length {
-            error!("current line length is not equal to the first line length");
-            return;
-        }
-        for c in line.chars() {
-            board.push(Symbol::from(c));
+    // `solve` is the default command
+    let skip = if command.as_deref() == Some("solve") {
+        2
+    } else {
+        1
+    };
+    let args = Args::parse(std::env::args().skip(skip)).unwrap_or_else(|e| {
+        report_usage_error(&e);
+        std::process::exit(EXIT_INVALID_INPUT);
+    });
+    let json = args.format.is_json();
+
+    let input = args.read_input().unwrap_or_else(|e| {
+        report_error(json, format!("failed to read {}: {}", args.source(), e));
+        std::process::exit(EXIT_INVALID_INPUT);
+    });
+
+    // puzzles separated by blank lines are solved one after the other
+    let puzzles = split_puzzles(&input);
+    if puzzles.len() > 1 {
+        if args.partial.is_some()
+            || args.verify.is_some()
+            || args.rate
+            || args.svg.is_some()
+            || args.cross_check.is_some()
+            || args.checkpoint.is_some()
+            || args.resume.is_some()
+        {
+            report_error(
+                json,
+                "--partial, --verify, --rate, --svg, --cross-check, --checkpoint and --resume \
+                 only take a single puzzle",
+            );
+            std::process::exit(EXIT_INVALID_INPUT);
         }
+        std::process::exit(solve_batch(&puzzles, &args));
     }
+    // a single puzzle is read without its header
+    let input = match &puzzles[..] {
+        [puzzle] => puzzle.text.clone(),
+        _ => input,
+    };
 
-    let counts = board.iter().counts();
-    if counts.contains_key(&Symbol::REnd) && counts[&Symbol::REnd] != 2 {
-        error!(
-            "There are {} R endpoints, but there should be 0 or 2",
-            counts[&Symbol::REnd]
-        );
-        return;
+    if let Some(path) = &args.verify {
+        let proposed = read_input(Some(path)).unwrap_or_else(|e| {
+            report_error(json, format!("failed to read {}: {}", path, e));
+            std::process::exit(EXIT_INVALID_INPUT);
+        });
+        std::process::exit(commands::verify::verify_one(
+            &input,
+            &proposed,
+            &args.symbols,
+            args.pad,
+            json,
+        ));
     }
-    if counts.contains_key(&Symbol::GEnd) && counts[&Symbol::GEnd] != 2 {
-        error!(
-            "There are {} G endpoints, but there should be 0 or 2",
-            counts[&Symbol::GEnd]
-        );
-        return;
+    if args.rate {
+        std::process::exit(commands::rate::rate_one(&input, &args));
     }
-    if counts.contains_key(&Symbol::BEnd) && counts[&Symbol::BEnd] != 2 {
-        error!(
-            "There are {} B endpoints, but there should be 0 or 2",
-            counts[&Symbol::BEnd]
-        );
-        return;
+
+    let partial = args.partial.as_deref().map(|path| {
+        read_input(Some(path)).unwrap_or_else(|e| {
+            report_error(json, format!("failed to read {}: {}", path, e));
+            std::process::exit(EXIT_INVALID_INPUT);
+        })
+    });
+    if let Err(e) = split_given(&input, partial.as_deref(), &args.symbols) {
+        report_usage_error(&e);
+        std::process::exit(EXIT_INVALID_INPUT);
     }
+    let solved = solve_one(&input, partial.as_deref(), &args).unwrap_or_else(|errors| {
+        report_errors(json, errors);
+        std::process::exit(EXIT_INVALID_INPUT);
+    });
+    let Solved {
+        board,
+        solutions,
+        elapsed,
+    } = &solved;
 
-    warn!("start solving");
+    // statistics go along the JSON document, or to stderr
+    let timed_out = board.stats().timed_out;
+    let stats = ((args.stats || timed_out) && json).then(|| solved.stats_json());
 
-    let now = std::time::Instant::now();
+    // `--quiet` only prints a solution, the verdict of `--unique` is told by
+    // the exit code
+    let (mode, shown) = match args.mode {
+        Mode::Unique if args.quiet => match &solutions[..] {
+            [_] if !timed_out => (Mode::First, &solutions[..]),
+            _ => (Mode::First, &[][..]),
+        },
+        mode => (mode, &solutions[..]),
+    };
+    if !(args.quiet && shown.is_empty()) {
+        let written = match &args.output {
+            Some(path) => File::create(path).and_then(|file| {
+                write_solutions(
+                    &mut BufWriter::new(file),
+                    board,
+                    shown,
+                    mode,
+                    args.format,
+                    stats.as_ref(),
+                )
+            }),
+            None => write_solutions(
+                &mut io::stdout(),
+                board,
+                shown,
+                mode,
+                args.format,
+                stats.as_ref(),
+            ),
+        };
+        if let Err(e) = written {
+            report_error(
+                json,
+                format!(
+                    "failed to write the result to {}: {}",
+                    args.output.as_deref().unwrap_or("stdout"),
+                    e
+                ),
+            );
+            std::process::exit(EXIT_INVALID_INPUT);
+        }
+    }
+
+    // the puzzle itself is drawn when there is no solution
+    if let Some(path) = &args.svg {
+        let image = match solutions.first() {
+            Some(solution) => solution.to_svg(board, args.cell_size),
+            None => board.to_svg(args.cell_size),
+        };
+        if let Err(e) = fs::write(path, image) {
+            report_error(
+                json,
+                format!("failed to write the image to {}: {}", path, e),
+            );
+            std::process::exit(EXIT_INVALID_INPUT);
+        }
+    }
 
-    let width = length;
-    let height = board.len() / width;
-    let mut lines = Vec::new();
-    for _ in 0..board.len() {
-        lines.push([None; 4]);
+    if !json {
+        solved.report_stats(&args);
+    }
+    if let Some(reason) = solved.explanation(&args) {
+        if json {
+            eprintln!("{}", Json::object([("explanation", reason.into())]));
+        } else {
+            eprintln!("reason: {}", reason);
+        }
+    }
+    info!("running takes {:?}", elapsed);
+    if args.verbose && !args.quiet {
+        eprintln!("Running takes {}.", format_duration(*elapsed));
     }
 
-    let mut board = Board {
-        board: board.into_iter().map(|s| (s, 0)).collect(),
-        lines,
-        result: Vec::new(),
-        width,
-        height,
+    let exit_code = match args.cross_check {
+        Some(checker) => cross_check_one(&input, partial.as_deref(), &args, &solved, checker),
+        None => None,
     };
+    std::process::exit(exit_code.unwrap_or(solved.exit_code(args.mode)));
+}
 
-    let res = solve_color(&mut board, Color::Red);
-    if res {
-        info!("solution found");
-        debug!("{:?}", board.board);
-        debug!("{:?}", board.lines);
-        for (color, group) in &board.result.iter().group_by(|s| s.2) {
-            println!("{}:", color);
-            for (point, direction, _) in group {
-                println!("{} {:?}", direction, point);
-            }
-        }
-    } else {
-        warn!("no solution");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_level_flags_are_unknown_arguments() {
+        let args = ["--level", "B12"].map(String::from);
+        let error = Args::parse(args.into_iter()).err().unwrap();
+        assert_eq!(error, "unknown argument: --level");
     }
 
-    let elapsed_time = now.elapsed();
-    println!("Running takes {} seconds.", elapsed_time.as_secs());
+    #[test]
+    fn given_segments_follow_a_dash_line() {
+        let input = "RrR\n---\nRed Right (0, 0)";
+        let symbols = Alphabet::default();
+        assert_eq!(
+            split_given(input, None, &symbols),
+            Ok(("RrR", Some("Red Right (0, 0)")))
+        );
+        assert_eq!(
+            split_given("RrR", Some("given"), &symbols),
+            Ok(("RrR", Some("given")))
+        );
+    }
+
+    #[test]
+    fn given_segments_cannot_also_come_from_partial() {
+        let input = "RrR\n---\nRed Right (0, 0)";
+        let error = split_given(input, Some("Red Left (2, 0)"), &Alphabet::default());
+        assert_eq!(
+            error,
+            Err("--partial cannot be used on a puzzle with given segments after a --- line".into())
+        );
+    }
+
+    #[test]
+    fn a_dash_row_is_not_a_separator_with_dash_empty_cells() {
+        let symbols = Alphabet::parse("[symbols]\nempty = \"-\"").unwrap();
+        let input = "R-R\n---\nR-R";
+        assert_eq!(split_given(input, None, &symbols), Ok((input, None)));
+        assert_eq!(
+            split_given(input, Some("Red Right (0, 0)"), &symbols),
+            Ok((input, Some("Red Right (0, 0)")))
+        );
+        assert_eq!(Board::parse_with(input, &symbols).unwrap().height(), 3);
+    }
 }
@@ -0,0 +1,32 @@
+use crate::{Board, Color, Direction, Point, SearchSnapshot};
+
+// Hooks the search calls as it draws and takes back lines, see
+// `Board::solve_observed`
+//
+// all do nothing by default, and `()` is the observer that does nothing at
+// all, which the search compiles away
+pub trait SearchObserver {
+    // a line from `pos` in `direction` was drawn on `board`
+    fn on_place(&mut self, _board: &Board, _pos: Point, _direction: Direction, _color: Color) {}
+
+    // the line from `pos` in `direction` was taken back from `board`
+    fn on_remove(&mut self, _board: &Board, _pos: Point, _direction: Direction, _color: Color) {}
+
+    // the line just drawn on `board` connected every node of `color`, the
+    // search going on with the next color; taking that line back is the
+    // next `on_remove` of this color
+    fn on_color_completed(&mut self, _board: &Board, _color: Color) {}
+
+    // whether to get the state of the search in `on_snapshot` now, asked
+    // between moves, and once with `stopping` when the search gives up
+    // before its end, on a timeout, a cancellation or the attempt budget
+    fn wants_snapshot(&mut self, _stopping: bool) -> bool {
+        false
+    }
+
+    // the state of the search when `wants_snapshot` asked for it, to resume
+    // it with `Board::resume_observed`
+    fn on_snapshot(&mut self, _snapshot: SearchSnapshot) {}
+}
+
+impl SearchObserver for () {}
@@ -0,0 +1,85 @@
+use log::info;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::solver::{search_moves, with_timeout};
+use crate::{Board, Direction, Stats};
+
+// search each move out of the first endpoint on its own copy of the board,
+// spread over `options.threads` threads, leaving the solution on `board`
+//
+// the solution kept is the one of the first move in search order, so the
+// result is the same as searching on a single thread unless it timed out
+//
+// the threads are scoped std threads taking the moves from a shared counter,
+// which shares the work of this single fork point as rayon would without
+// adding it as a dependency
+pub(crate) fn solve_parallel(board: &mut Board, moves: Option<Vec<Direction>>) -> bool {
+    let Some(moves) = moves else {
+        return search_moves(
+            board,
+            &mut |_| true,
+            None,
+            &AtomicBool::new(false),
+            &mut (),
+            true,
+        );
+    };
+
+    // index of the first move known to lead to a solution
+    let best = AtomicUsize::new(moves.len());
+    let cancel = moves
+        .iter()
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+    let solutions = Mutex::new(vec![None; moves.len()]);
+    let stats = Mutex::new(Stats::default());
+    let next = AtomicUsize::new(0);
+    let (_, timed_out) = with_timeout(board.options.timeout, &cancel, || {
+        std::thread::scope(|scope| {
+            for _ in 0..board.options.threads.min(moves.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= moves.len() {
+                        break;
+                    }
+                    if best.load(Ordering::Relaxed) < i {
+                        continue;
+                    }
+                    let mut board = board.clone();
+                    board.stats = Stats::default();
+                    let solved = search_moves(
+                        &mut board,
+                        &mut |_| true,
+                        Some(&moves[i..=i]),
+                        &cancel[i],
+                        &mut (),
+                        true,
+                    );
+                    stats.lock().unwrap().merge(&board.stats);
+                    if solved {
+                        info!("first move {} leads to a solution", moves[i]);
+                        best.fetch_min(i, Ordering::Relaxed);
+                        for cancel in &cancel[i + 1..] {
+                            cancel.store(true, Ordering::Relaxed);
+                        }
+                        solutions.lock().unwrap()[i] = Some(board);
+                    }
+                });
+            }
+        })
+    });
+
+    let mut total = board.stats.clone();
+    total.merge(&stats.into_inner().unwrap());
+    let solved = match solutions.into_inner().unwrap().into_iter().flatten().next() {
+        Some(solved) => {
+            *board = solved;
+            true
+        }
+        None => false,
+    };
+    total.timed_out |= timed_out && !solved;
+    board.stats = total;
+    solved
+}
@@ -0,0 +1,781 @@
+use strum::IntoEnumIterator;
+
+use crate::symbol::MAX_WHITE;
+use crate::{Board, Color, ColorSet, Direction, Json, Point, Symbol};
+
+// Malformed puzzle text, lines and columns are 1-based
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // no line with any node
+    Empty,
+    // a character that is no node, with its line and column when known
+    InvalidSymbol {
+        symbol: char,
+        position: Option<(usize, usize)>,
+    },
+    // a character in the color list of a white node that is no color
+    InvalidColor {
+        color: char,
+        line: usize,
+        column: usize,
+    },
+    // a color list of a white node without its closing `]`
+    UnclosedColors {
+        line: usize,
+        column: usize,
+    },
+    // a line whose number of nodes differs from the first line
+    RaggedLine {
+        line: usize,
+        length: usize,
+        expected: usize,
+    },
+    // a number that is no possible white node, 0 or above `MAX_WHITE`
+    WhiteOutOfRange {
+        value: String,
+        line: usize,
+        column: usize,
+    },
+    // a line of segments in none of the formats the solver prints
+    InvalidSegment {
+        line: usize,
+        text: String,
+    },
+    // segments in JSON that cannot be read, at this byte offset
+    InvalidJson {
+        offset: usize,
+    },
+    // a line of a `[symbols]` table that maps no character to a node kind
+    InvalidAlphabet {
+        line: usize,
+        text: String,
+    },
+    // a saved search whose field at this path, as in `stack[3].tried`, is
+    // missing or holds no possible value
+    InvalidSnapshot {
+        field: String,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "no input"),
+            ParseError::InvalidSymbol {
+                symbol,
+                position: Some((line, column)),
+            } => write!(
+                f,
+                "invalid symbol {:?} at line {}, column {}",
+                symbol, line, column
+            ),
+            ParseError::InvalidSymbol {
+                symbol,
+                position: None,
+            } => write!(f, "invalid symbol {:?}", symbol),
+            ParseError::InvalidColor {
+                color,
+                line,
+                column,
+            } => write!(
+                f,
+                "invalid color {:?} at line {}, column {}",
+                color, line, column
+            ),
+            ParseError::UnclosedColors { line, column } => write!(
+                f,
+                "color list opened at line {}, column {} is not closed",
+                line, column
+            ),
+            ParseError::RaggedLine {
+                line,
+                length,
+                expected,
+            } => write!(
+                f,
+                "line {} has length {} but the first line has length {}",
+                line, length, expected
+            ),
+            ParseError::WhiteOutOfRange {
+                value,
+                line,
+                column,
+            } => write!(
+                f,
+                "white node {} at line {}, column {} is out of range, it is passed 1 to {} \
+                 times as a node has only 8 neighbors",
+                value, line, column, MAX_WHITE
+            ),
+            ParseError::InvalidSegment { line, text } => {
+                write!(f, "invalid segment {:?} at line {}", text, line)
+            }
+            ParseError::InvalidJson { offset } => {
+                write!(f, "invalid JSON segments at byte {}", offset)
+            }
+            ParseError::InvalidAlphabet { line, text } => {
+                write!(f, "invalid symbol mapping {:?} at line {}", text, line)
+            }
+            ParseError::InvalidSnapshot { field } => {
+                write!(f, "invalid saved search, {} is missing or malformed", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Shapes of the nodes in the game, filled for the nodes of a color and
+// hollow for its endpoints
+const GLYPHS: [(char, Symbol); 6] = [
+    ('▲', Symbol::Node(Color::Red)),
+    ('△', Symbol::End(Color::Red)),
+    ('■', Symbol::Node(Color::Green)),
+    ('□', Symbol::End(Color::Green)),
+    ('◆', Symbol::Node(Color::Blue)),
+    ('◇', Symbol::End(Color::Blue)),
+];
+
+// What a character of the text format stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    // a node of its own, by letter, `.` or shape
+    Node(Symbol),
+    // a white node passed up to 9 times
+    Digit(u8),
+    // `◯` before the count of a white node, as in `◯2`
+    WhiteMark,
+    // `(` before the count of a white node, as in `(3)`
+    OpenCount,
+    Whitespace,
+    Other,
+}
+
+impl CharClass {
+    fn of(c: char) -> CharClass {
+        match c {
+            'a'..='z' => CharClass::Node(Symbol::Node(Color::from_letter(c).unwrap())),
+            'A'..='Z' => CharClass::Node(Symbol::End(Color::from_letter(c).unwrap())),
+            '.' => CharClass::Node(Symbol::Empty),
+            '1'..='9' => CharClass::Digit(c as u8 - b'0'),
+            '◯' | '○' => CharClass::WhiteMark,
+            '(' => CharClass::OpenCount,
+            c if c.is_whitespace() => CharClass::Whitespace,
+            c => match GLYPHS.iter().find(|&&(glyph, _)| glyph == c) {
+                Some(&(_, symbol)) => CharClass::Node(symbol),
+                None => CharClass::Other,
+            },
+        }
+    }
+}
+
+impl TryFrom<char> for Symbol {
+    type Error = ParseError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match CharClass::of(c) {
+            CharClass::Node(symbol) => Ok(symbol),
+            CharClass::Digit(n) => Ok(Symbol::White(n)),
+            _ => Err(ParseError::InvalidSymbol {
+                symbol: c,
+                position: None,
+            }),
+        }
+    }
+}
+
+impl Board {
+    // parse a puzzle, one line of nodes per row
+    //
+    // the nodes are either one character each, a letter or the shape of the
+    // node in the game, e.g. `▲` or `△` for red, or tokens separated by
+    // whitespace, e.g. `r G 12 . b`, which is assumed as soon as a line has
+    // whitespace between nodes. Whitespace around the nodes of a line is
+    // ignored in both
+    //
+    // lines starting with `#` are comments, and they, blank lines and `\r` of
+    // Windows line endings are ignored
+    pub fn parse(text: &str) -> Result<Board, ParseError> {
+        parse_board(text, false)
+    }
+
+    // parse a puzzle as `Board::parse`, filling the lines shorter than the
+    // longest one with empty cells, as when trailing dots were trimmed
+    pub fn parse_padded(text: &str) -> Result<Board, ParseError> {
+        parse_board(text, true)
+    }
+}
+
+// read the puzzle `text` for `Board::parse`, or `Board::parse_padded` with
+// `pad`
+fn parse_board(text: &str, pad: bool) -> Result<Board, ParseError> {
+    let lines = text
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim_start();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .collect::<Vec<_>>();
+    let tokens = lines
+        .iter()
+        .any(|(_, line)| line.trim().contains(char::is_whitespace));
+    if lines.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let rows = lines
+        .into_iter()
+        .map(|(number, line)| {
+            let nodes = if tokens {
+                parse_tokens(line, number + 1)?
+            } else {
+                parse_line(line, number + 1)?
+            };
+            Ok((number, nodes))
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    let width = if pad {
+        rows.iter().map(|(_, nodes)| nodes.len()).max().unwrap_or(0)
+    } else {
+        rows[0].1.len()
+    };
+    let mut nodes = Vec::new();
+    for (number, mut line_nodes) in rows {
+        if pad {
+            line_nodes.resize(width, (Symbol::Empty, ColorSet::ALL));
+        } else if line_nodes.len() != width {
+            return Err(ParseError::RaggedLine {
+                line: number + 1,
+                length: line_nodes.len(),
+                expected: width,
+            });
+        }
+        nodes.extend(line_nodes);
+    }
+    if width == 0 {
+        return Err(ParseError::Empty);
+    }
+
+    let (symbols, allowed): (Vec<_>, Vec<_>) = nodes.into_iter().unzip();
+    let mut board = Board::new(width, symbols);
+    board.allowed = allowed;
+    Ok(board)
+}
+
+impl std::str::FromStr for Board {
+    type Err = ParseError;
+
+    // same as `Board::parse`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Board::parse(text)
+    }
+}
+
+// write the puzzle in the text format `Board::parse` reads
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for index in 0..self.board.len() {
+            if index > 0 && index % self.width == 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", self.node_text(index))?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    // the node at `index` in the text format, with the colors allowed
+    // through it if they are restricted, e.g. `2[rb]`
+    pub(crate) fn node_text(&self, index: usize) -> String {
+        let mut text = self.board[index].0.to_string();
+        let allowed = self.allowed[index];
+        if allowed != ColorSet::ALL {
+            text.push('[');
+            for color in Color::iter().filter(|&c| allowed.contains(c)) {
+                text.push(color.letter());
+            }
+            text.push(']');
+        }
+        text
+    }
+}
+
+// read a single node in the text format, as written by `Board::node_text`
+pub(crate) fn parse_node(text: &str) -> Option<(Symbol, ColorSet)> {
+    match parse_line(text, 1).ok()?[..] {
+        [node] => Some(node),
+        _ => None,
+    }
+}
+
+// parse the line `line_number` of the puzzle into its nodes, skipping
+// whitespace around them
+//
+// the count of a white node may also be written in parentheses, e.g. `(3)`,
+// it may follow a `◯` as in the game, and it may be followed
+// by the colors allowed through it, e.g. `2[rb]`
+fn parse_line(line: &str, line_number: usize) -> Result<Vec<(Symbol, ColorSet)>, ParseError> {
+    let mut nodes = Vec::new();
+    let mut chars = line.chars().enumerate().peekable();
+    while let Some((column, c)) = chars.next() {
+        let (column, c) = match CharClass::of(c) {
+            CharClass::Whitespace => continue,
+            CharClass::WhiteMark => chars
+                .next_if(|&(_, c)| {
+                    matches!(CharClass::of(c), CharClass::Digit(_) | CharClass::OpenCount)
+                })
+                .ok_or(ParseError::InvalidSymbol {
+                    symbol: c,
+                    position: Some((line_number, column + 1)),
+                })?,
+            _ => (column, c),
+        };
+        let symbol = if CharClass::of(c) == CharClass::OpenCount {
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, ')')) => break,
+                    Some((_, c)) => value.push(c),
+                    None => {
+                        return Err(ParseError::InvalidSymbol {
+                            symbol: c,
+                            position: Some((line_number, column + 1)),
+                        })
+                    }
+                }
+            }
+            match value.parse::<u8>() {
+                Ok(n @ 1..=MAX_WHITE) => Symbol::White(n),
+                _ => {
+                    return Err(ParseError::WhiteOutOfRange {
+                        value,
+                        line: line_number,
+                        column: column + 1,
+                    })
+                }
+            }
+        } else if let CharClass::Digit(n) = CharClass::of(c) {
+            if n > MAX_WHITE {
+                return Err(ParseError::WhiteOutOfRange {
+                    value: n.to_string(),
+                    line: line_number,
+                    column: column + 1,
+                });
+            }
+            Symbol::White(n)
+        } else {
+            Symbol::try_from(c).map_err(|_| ParseError::InvalidSymbol {
+                symbol: c,
+                position: Some((line_number, column + 1)),
+            })?
+        };
+        let mut allowed = ColorSet::ALL;
+        if matches!(symbol, Symbol::White(_)) && chars.next_if(|&(_, c)| c == '[').is_some() {
+            allowed = ColorSet::empty();
+            loop {
+                match chars.next() {
+                    Some((_, ']')) => break,
+                    Some((i, c)) => {
+                        let color = Symbol::try_from(c)
+                            .ok()
+                            .filter(|s| !s.is_end())
+                            .and_then(|s| s.node_color())
+                            .ok_or(ParseError::InvalidColor {
+                                color: c,
+                                line: line_number,
+                                column: i + 1,
+                            })?;
+                        allowed.insert(color);
+                    }
+                    None => {
+                        return Err(ParseError::UnclosedColors {
+                            line: line_number,
+                            column: column + 2,
+                        })
+                    }
+                }
+            }
+        }
+        nodes.push((symbol, allowed));
+    }
+    Ok(nodes)
+}
+
+// parse the line `line_number` of a puzzle in the token format, where a
+// number of any width is a white node, after a `◯` or not, followed by the
+// colors allowed through it in brackets or not, `2[rb]` or `2rb`, and any
+// other token is one node in the character format
+fn parse_tokens(line: &str, line_number: usize) -> Result<Vec<(Symbol, ColorSet)>, ParseError> {
+    let mut nodes = Vec::new();
+    let mut column = 0;
+    for token in line.split_whitespace() {
+        // the 1-based column the token starts at, counted in characters
+        let start = line[column..].find(token).unwrap() + column;
+        column = start + token.len();
+        let token_column = line[..start].chars().count() + 1;
+
+        let number = token
+            .strip_prefix(|c| CharClass::of(c) == CharClass::WhiteMark)
+            .unwrap_or(token);
+        let digits = number
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(number.len());
+        if digits > 0 {
+            match number[..digits].parse::<u8>() {
+                Ok(n @ 1..=MAX_WHITE) => {
+                    // the character form takes the color list if any, which
+                    // may also come without brackets as in `2rb`, a token
+                    // being a single node
+                    let colors = &number[digits..];
+                    let rest =
+                        if !colors.is_empty() && colors.chars().all(|c| c.is_ascii_lowercase()) {
+                            format!("{}[{}]", Symbol::White(n), colors)
+                        } else {
+                            format!("{}{}", Symbol::White(n), colors)
+                        };
+                    nodes.push(parse_token(&rest, token, line_number, token_column)?);
+                }
+                _ => {
+                    return Err(ParseError::WhiteOutOfRange {
+                        value: number[..digits].to_string(),
+                        line: line_number,
+                        column: token_column,
+                    })
+                }
+            }
+        } else {
+            nodes.push(parse_token(token, token, line_number, token_column)?);
+        }
+    }
+    Ok(nodes)
+}
+
+// parse a token holding one node in the character format, reporting errors
+// at `column` of the line
+fn parse_token(
+    text: &str,
+    token: &str,
+    line_number: usize,
+    column: usize,
+) -> Result<(Symbol, ColorSet), ParseError> {
+    // columns inside the token count from where it starts on the line
+    let at_column = |e| match e {
+        ParseError::InvalidSymbol {
+            symbol,
+            position: Some((_, inner)),
+        } => ParseError::InvalidSymbol {
+            symbol,
+            position: Some((line_number, column + inner - 1)),
+        },
+        ParseError::InvalidColor {
+            color,
+            column: inner,
+            ..
+        } => ParseError::InvalidColor {
+            color,
+            line: line_number,
+            column: column + inner - 1,
+        },
+        ParseError::UnclosedColors { column: inner, .. } => ParseError::UnclosedColors {
+            line: line_number,
+            column: column + inner - 1,
+        },
+        ParseError::WhiteOutOfRange {
+            value,
+            column: inner,
+            ..
+        } => ParseError::WhiteOutOfRange {
+            value,
+            line: line_number,
+            column: column + inner - 1,
+        },
+        e => e,
+    };
+    match parse_line(text, line_number).map_err(at_column)?[..] {
+        [node] => Ok(node),
+        // a second node without whitespace before it
+        _ => Err(ParseError::InvalidSymbol {
+            symbol: token.chars().nth(1).unwrap_or(' '),
+            position: Some((line_number, column + 1)),
+        }),
+    }
+}
+
+// read segments in one of the forms the solver prints, line numbers
+// counting from the start of `text`:
+//
+// - paths, `Red: (0,0) -> (1,0) -> (2,1)`
+// - segments grouped by color, a `Red:` line followed by `Right (0, 0)` lines
+// - one segment per line, `Red Right (0, 0)`
+// - JSON, `[{"color": "Red", "from": [0, 0], "to": [1, 0]}, ..]`, alone or
+//   under `"segments"` in a document
+pub fn parse_segments(text: &str) -> Result<Vec<(Point, Direction, Color)>, ParseError> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        return parse_json_segments(text);
+    }
+
+    let mut segments = Vec::new();
+    let mut group = None;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || ParseError::InvalidSegment {
+            line: i + 1,
+            text: line.to_string(),
+        };
+        let (head, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        if let Some(color) = head.strip_suffix(':').and_then(color_named) {
+            if rest.is_empty() {
+                group = Some(color);
+                continue;
+            }
+            let points = rest
+                .split("->")
+                .map(|p| parse_point(p).ok_or_else(invalid))
+                .collect::<Result<Vec<_>, _>>()?;
+            for pair in points.windows(2) {
+                let offset = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+                let direction = Direction::from_offset(offset).ok_or_else(invalid)?;
+                segments.push((pair[0], direction, color));
+            }
+        } else if let Some(color) = color_named(head) {
+            let (direction, point) = rest.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let direction = direction_named(direction).ok_or_else(invalid)?;
+            segments.push((parse_point(point).ok_or_else(invalid)?, direction, color));
+        } else {
+            let color = group.ok_or_else(invalid)?;
+            let direction = direction_named(head).ok_or_else(invalid)?;
+            segments.push((parse_point(rest).ok_or_else(invalid)?, direction, color));
+        }
+    }
+    Ok(segments)
+}
+
+fn parse_json_segments(text: &str) -> Result<Vec<(Point, Direction, Color)>, ParseError> {
+    let json = Json::parse(text).map_err(|offset| ParseError::InvalidJson { offset })?;
+    json_segments(&json)
+}
+
+// the segments of a JSON document, alone or under `"segments"`
+pub(crate) fn json_segments(json: &Json) -> Result<Vec<(Point, Direction, Color)>, ParseError> {
+    let invalid = || ParseError::InvalidJson { offset: 0 };
+    let Some(Json::Array(values)) = json.get("segments").or(Some(json)) else {
+        return Err(invalid());
+    };
+    values
+        .iter()
+        .map(|value| {
+            let color = match value.get("color") {
+                Some(Json::String(name)) => color_named(name),
+                _ => None,
+            };
+            let from = value.get("from").and_then(json_point);
+            let to = value.get("to").and_then(json_point);
+            match (color, from, to) {
+                (Some(color), Some(from), Some(to)) => {
+                    Direction::from_offset((to.0 - from.0, to.1 - from.1))
+                        .map(|direction| (from, direction, color))
+                        .ok_or_else(invalid)
+                }
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn color_named(name: &str) -> Option<Color> {
+    Color::iter().find(|c| c.to_string() == name)
+}
+
+pub(crate) fn direction_named(name: &str) -> Option<Direction> {
+    Direction::iter().find(|d| d.to_string() == name)
+}
+
+// `(x,y)` or `(x, y)`
+fn parse_point(text: &str) -> Option<Point> {
+    let (x, y) = text
+        .trim()
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+pub(crate) fn json_point(json: &Json) -> Option<Point> {
+    match json {
+        Json::Array(values) => match values[..] {
+            [Json::Number(x), Json::Number(y)] if x.fract() == 0.0 && y.fract() == 0.0 => {
+                Some((x as i32, y as i32))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Alphabet;
+
+    #[test]
+    fn a_valid_board_parses_to_its_size_and_nodes() {
+        let board = Board::parse("R2B\r\n2Gr\r\n\n\n").unwrap();
+        assert_eq!((board.width(), board.height()), (3, 2));
+        assert_eq!(
+            board.symbols().collect::<Vec<_>>(),
+            [
+                Symbol::End(Color::Red),
+                Symbol::White(2),
+                Symbol::End(Color::Blue),
+                Symbol::White(2),
+                Symbol::End(Color::Green),
+                Symbol::Node(Color::Red),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Board::parse("").unwrap_err(), ParseError::Empty);
+        assert_eq!(
+            Board::parse("# a comment\n\n").unwrap_err(),
+            ParseError::Empty
+        );
+    }
+
+    #[test]
+    fn invalid_symbol() {
+        assert_eq!(
+            Board::parse("RrR\nR!R").unwrap_err(),
+            ParseError::InvalidSymbol {
+                symbol: '!',
+                position: Some((2, 2)),
+            }
+        );
+        assert_eq!(
+            Symbol::try_from('!').unwrap_err(),
+            ParseError::InvalidSymbol {
+                symbol: '!',
+                position: None,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_color() {
+        assert_eq!(
+            Board::parse("R2[r!]R").unwrap_err(),
+            ParseError::InvalidColor {
+                color: '!',
+                line: 1,
+                column: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn unclosed_colors() {
+        assert_eq!(
+            Board::parse("R2[rb").unwrap_err(),
+            ParseError::UnclosedColors { line: 1, column: 3 }
+        );
+    }
+
+    #[test]
+    fn ragged_line() {
+        assert_eq!(
+            Board::parse("RrR\nRr").unwrap_err(),
+            ParseError::RaggedLine {
+                line: 2,
+                length: 2,
+                expected: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn white_out_of_range() {
+        assert_eq!(
+            Board::parse("R (0) R").unwrap_err(),
+            ParseError::WhiteOutOfRange {
+                value: "0".into(),
+                line: 1,
+                column: 3,
+            }
+        );
+        // a node has only 8 neighbors, so it is passed at most 4 times
+        for (text, value, column) in [("R 5 R", "5", 3), ("R(200)R", "200", 2), ("r\nR7R", "7", 2)]
+        {
+            let error = Board::parse(text).unwrap_err();
+            assert_eq!(
+                error,
+                ParseError::WhiteOutOfRange {
+                    value: value.into(),
+                    line: text.lines().count(),
+                    column,
+                }
+            );
+            assert!(error.to_string().contains("only 8 neighbors"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn invalid_segment() {
+        assert_eq!(
+            parse_segments("Red Right (0, 0)\nRed sideways (1, 0)").unwrap_err(),
+            ParseError::InvalidSegment {
+                line: 2,
+                text: "Red sideways (1, 0)".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(matches!(
+            parse_segments("[{\"color\": \"Red\"").unwrap_err(),
+            ParseError::InvalidJson { .. }
+        ));
+    }
+
+    #[test]
+    fn invalid_alphabet() {
+        assert_eq!(
+            Alphabet::parse("[symbols]\nred = \"tt\"").unwrap_err(),
+            ParseError::InvalidAlphabet {
+                line: 2,
+                text: "red = \"tt\"".into(),
+            }
+        );
+    }
+
+    // `Board` has no `PartialEq`, the text it writes holds all it reads
+    fn same_board(a: &Board, b: &Board) {
+        assert_eq!((a.width(), a.height()), (b.width(), b.height()));
+        assert_eq!(
+            a.symbols().collect::<Vec<_>>(),
+            b.symbols().collect::<Vec<_>>()
+        );
+        assert_eq!(a.allowed, b.allowed);
+        assert_eq!(a.colors(), b.colors());
+    }
+
+    #[test]
+    fn both_formats_give_the_same_board() {
+        let chars = Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap();
+        let tokens = Board::parse("# the same puzzle\nR 2 B\n2 G r\n  g b R\n. G B  ").unwrap();
+        same_board(&chars, &tokens);
+
+        let chars = Board::parse("R(4)2[rb]\n◯3▲△").unwrap();
+        let tokens = Board::parse("R 4 2rb\n◯3 ▲ △").unwrap();
+        same_board(&chars, &tokens);
+        assert_eq!(tokens.symbol((1, 0)), Some(Symbol::White(4)));
+    }
+}
@@ -0,0 +1,100 @@
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+use crate::clock::Instant;
+use crate::{Board, Color, Direction, Point, SearchObserver};
+
+// Moves between two looks at the clock of `ProgressObserver`
+const MOVES_PER_CHECK: u32 = 4096;
+
+// Where a search stands, see `ProgressObserver`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub elapsed: Duration,
+    // calls to `add_line` so far, legal or not
+    pub attempts: u64,
+    pub backtracks: u64,
+    // the color whose path is being drawn
+    pub color: Option<Color>,
+    // lines on the board
+    pub depth: usize,
+    // a crude estimate of the share of the search done, from the first move
+    // out of the first endpoint among those staying on the board
+    pub done: f64,
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} s, {} attempts, {} backtracks, ",
+            self.elapsed.as_secs_f64(),
+            self.attempts,
+            self.backtracks
+        )?;
+        if let Some(color) = self.color {
+            write!(f, "color {}, ", color)?;
+        }
+        write!(f, "depth {}, ~{:.0}% done", self.depth, self.done * 100.0)
+    }
+}
+
+// An observer calling `report` with the progress of the search about every
+// `interval`
+//
+// the clock is only read every few thousand moves, so a search watched this
+// way is barely slower
+pub struct ProgressObserver<F: FnMut(&Progress)> {
+    report: F,
+    interval: Duration,
+    start: Instant,
+    last: Instant,
+    moves: u32,
+}
+
+impl<F: FnMut(&Progress)> ProgressObserver<F> {
+    pub fn new(interval: Duration, report: F) -> Self {
+        ProgressObserver {
+            report,
+            interval,
+            start: Instant::now(),
+            last: Instant::now(),
+            moves: 0,
+        }
+    }
+
+    fn tick(&mut self, board: &Board) {
+        self.moves += 1;
+        if !self.moves.is_multiple_of(MOVES_PER_CHECK) || self.last.elapsed() < self.interval {
+            return;
+        }
+        self.last = Instant::now();
+        // the first move the search took, among the moves staying on the
+        // board from where it started
+        let first = board.result.get(board.frozen).map(|&(pos, direction, _)| {
+            let moves = Direction::iter()
+                .filter(|d| board.in_bounds(d.apply_offset(pos)))
+                .collect::<Vec<_>>();
+            let taken = moves.iter().position(|&d| d == direction).unwrap_or(0);
+            taken as f64 / moves.len() as f64
+        });
+        (self.report)(&Progress {
+            elapsed: self.start.elapsed(),
+            attempts: board.stats.add_line_calls,
+            backtracks: board.stats.backtracks,
+            color: board.result.last().map(|line| line.2),
+            depth: board.result.len(),
+            done: first.unwrap_or(0.0),
+        });
+    }
+}
+
+impl<F: FnMut(&Progress)> SearchObserver for ProgressObserver<F> {
+    fn on_place(&mut self, board: &Board, _pos: Point, _direction: Direction, _color: Color) {
+        self.tick(board);
+    }
+
+    fn on_remove(&mut self, board: &Board, _pos: Point, _direction: Direction, _color: Color) {
+        self.tick(board);
+    }
+}
@@ -0,0 +1,157 @@
+use strum::IntoEnumIterator;
+
+use crate::{Board, Color, Direction, Point, Symbol};
+
+impl Board {
+    // whether the path of `color` drawn up to `head` can still reach every
+    // node of `color` it has not covered yet and its other endpoint
+    //
+    // lines only ever get added while a path grows, so a line illegal now
+    // stays illegal and a flood fill over the lines `can_add_line` accepts
+    // on the current board never misses a valid continuation
+    pub(crate) fn remaining_reachable(&self, head: Point, color: Color) -> bool {
+        let mut visited = vec![false; self.board.len()];
+        let mut stack = vec![head];
+        visited[self.index(head)] = true;
+        while let Some(point) = stack.pop() {
+            if point != head && self.symbol(point) == Some(Symbol::color_end(color)) {
+                // paths stop at the endpoints
+                continue;
+            }
+            for direction in Direction::iter() {
+                if !self.can_add_line(point, direction, color) {
+                    continue;
+                }
+                let next_point = direction.apply_offset(point);
+                let next_index = self.index(next_point);
+                if !visited[next_index] {
+                    visited[next_index] = true;
+                    stack.push(next_point);
+                }
+            }
+        }
+
+        self.board
+            .iter()
+            .zip(&visited)
+            .all(|(&(symbol, counter), &visited)| {
+                let remaining = counter == 0
+                    && (symbol == Symbol::color(color) || symbol == Symbol::color_end(color));
+                !remaining || visited
+            })
+    }
+}
+
+impl Board {
+    // whether the white node at `pos` can still get the two lines per pass
+    // it needs from the lines it has and the incident lines still free
+    pub(crate) fn white_feasible(&self, pos: Point) -> bool {
+        let index = self.index(pos);
+        let Symbol::White(n) = self.board[index].0 else {
+            return true;
+        };
+        let used = self.degree[index];
+        let achievable = Direction::iter()
+            .filter(|&direction| self.line_achievable(pos, direction))
+            .count() as u8;
+        u16::from(used + achievable) >= 2 * u16::from(n)
+    }
+
+    // whether every white node touching the line between `from` and `to`,
+    // or a line that it blocks, can still be satisfied
+    pub(crate) fn whites_feasible_around(&self, from: Point, to: Point) -> bool {
+        [from, to].into_iter().all(|point| {
+            std::iter::once(point)
+                .chain(Direction::iter().map(|d| d.apply_offset(point)))
+                .all(|p| !self.in_bounds(p) || self.white_feasible(p))
+        })
+    }
+
+    // whether some line may still be drawn from the white node at `pos`
+    // in `direction`, ignoring which path would draw it
+    pub(crate) fn line_achievable(&self, pos: Point, direction: Direction) -> bool {
+        let next_point = direction.apply_offset(pos);
+        if !self.in_bounds(next_point) {
+            return false;
+        }
+        let (store_pos, direction_inner) = direction.store(pos);
+        if self.lines[self.index(store_pos)][direction_inner as usize].is_some() {
+            return false;
+        }
+        if let Some((conflict_point, direction_inner)) = direction.may_conflict(pos) {
+            if self.lines[self.index(conflict_point)][direction_inner as usize].is_some() {
+                return false;
+            }
+        }
+        let allowed = self.allowed[self.index(pos)];
+        let next_index = self.index(next_point);
+        let degree = self.degree[next_index];
+        match self.board[next_index].0 {
+            Symbol::Empty => false,
+            Symbol::White(m) => {
+                u16::from(degree) < 2 * u16::from(m)
+                    && self
+                        .colors
+                        .iter()
+                        .any(|&c| allowed.contains(c) && self.allowed[next_index].contains(c))
+            }
+            symbol => {
+                let full = if symbol.is_end() { 1 } else { 2 };
+                degree < full && symbol.node_color().is_some_and(|c| allowed.contains(c))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::Rng;
+    use crate::{Board, SolveOptions};
+
+    // a 5x5 board with eight white nodes of 2 and 3
+    const WHITES: &str = "bb2R.\nb222R\nB32Br\nG222.\nGgggg";
+
+    fn solved(text: &str, prune: bool) -> Board {
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            prune,
+            threads: 1,
+            ..SolveOptions::default()
+        });
+        board.solve();
+        board
+    }
+
+    fn solves(text: &str, prune: bool) -> bool {
+        solved(text, prune).is_solved()
+    }
+
+    #[test]
+    fn pruning_never_changes_the_verdict() {
+        let mut rng = Rng(762);
+        let mut solvable = 0;
+        for _ in 0..300 {
+            let width = 3 + rng.below(2);
+            let text = rng.board_text(width, 3);
+            let verdict = solves(&text, false);
+            assert_eq!(solves(&text, true), verdict, "{}", text);
+            solvable += verdict as usize;
+        }
+        // the boards are a mix of both verdicts
+        assert!(solvable > 0 && solvable < 300, "{} solvable", solvable);
+    }
+
+    #[test]
+    fn pruning_cuts_the_search_on_white_nodes() {
+        let pruned = solved(WHITES, true);
+        let unpruned = solved(WHITES, false);
+        assert!(pruned.is_solved() && unpruned.is_solved());
+        let (pruned, unpruned) = (pruned.stats(), unpruned.stats());
+        assert!(
+            pruned.add_line_calls * 10 < unpruned.add_line_calls,
+            "{} attempts pruned, {} unpruned",
+            pruned.add_line_calls,
+            unpruned.add_line_calls
+        );
+    }
+}
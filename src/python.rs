@@ -0,0 +1,84 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::Board;
+
+// `lyne.Board`, a puzzle read from its text that Python code can check and
+// solve without going through the command line
+//
+// bad input raises `ValueError` with the message the command line prints
+#[pyclass(name = "Board", module = "lyne", frozen)]
+struct PyBoard {
+    board: Board,
+}
+
+#[pymethods]
+impl PyBoard {
+    // read a puzzle in the text format of the command line
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<Self> {
+        let board = Board::parse(text).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBoard { board })
+    }
+
+    // raise `ValueError` listing every problem of `Board::validate`, one per
+    // line, if the board is obviously unsolvable
+    fn validate(&self) -> PyResult<()> {
+        self.board
+            .validate()
+            .into_result()
+            .map_err(|report| PyValueError::new_err(report.to_string()))
+    }
+
+    // the path of each color as `(color, [(x, y), ..])` from one endpoint to
+    // the other, or None if there is no solution
+    //
+    // the board is validated first, and the search runs without the GIL so
+    // other Python threads go on meanwhile
+    #[allow(clippy::type_complexity)]
+    fn solve(&self, py: Python<'_>) -> PyResult<Option<Vec<(String, Vec<(i32, i32)>)>>> {
+        self.validate()?;
+        let mut board = self.board.clone();
+        let solution = py.detach(move || board.solve());
+        Ok(solution.map(|solution| {
+            solution
+                .paths()
+                .into_iter()
+                .map(|(color, path)| (color.to_string(), path))
+                .collect()
+        }))
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.board.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.board.height()
+    }
+
+    // the nodes row by row, as written in the text format, e.g. `R` or `2`
+    #[getter]
+    fn symbols(&self) -> Vec<Vec<String>> {
+        let symbols = self
+            .board
+            .symbols()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        symbols
+            .chunks(self.board.width())
+            .map(|row| row.to_vec())
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Board({}x{})", self.board.width(), self.board.height())
+    }
+}
+
+#[pymodule]
+fn lyne(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBoard>()
+}
@@ -0,0 +1,149 @@
+use crate::{Board, Color, Direction, Point, Solution};
+
+impl Color {
+    // ANSI escape code of the terminal color
+    fn ansi(self) -> String {
+        match self {
+            Color::Red => "\x1b[31m".into(),
+            Color::Green => "\x1b[32m".into(),
+            Color::Blue => "\x1b[34m".into(),
+            Color::Yellow => "\x1b[33m".into(),
+            Color::Cyan => "\x1b[36m".into(),
+            // no basic ANSI color is orange, take it from the 256 colors
+            Color::Orange => "\x1b[38;5;208m".into(),
+            // the other colors spread over the 6x6x6 color cube
+            _ => format!("\x1b[38;5;{}m", 16 + self.index() * 37 % 216),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const REVERSE: &str = "\x1b[7m";
+
+impl Solution {
+    // draw the solution on the grid of the board
+    //
+    // nodes sit on even rows and columns, the lines between them are drawn
+    // in the gaps with box-drawing characters
+    pub fn render(&self, board: &Board) -> String {
+        render_grid(board, self.segments(), None, false)
+    }
+
+    // same as `render`, with lines and nodes colored for a terminal
+    pub fn render_ansi(&self, board: &Board) -> String {
+        render_grid(board, self.segments(), None, true)
+    }
+}
+
+impl Board {
+    // draw the lines on the board so far, as `Solution::render` does
+    pub fn render(&self) -> String {
+        render_grid(self, &self.result, None, false)
+    }
+
+    // draw the lines on the board so far for a terminal, with the node at
+    // `highlight` in reverse video
+    pub fn render_ansi(&self, highlight: Option<Point>) -> String {
+        render_grid(self, &self.result, highlight, true)
+    }
+}
+
+fn render_grid(
+    board: &Board,
+    segments: &[(Point, Direction, Color)],
+    highlight: Option<Point>,
+    ansi: bool,
+) -> String {
+    let (width, height) = (board.width() * 2 - 1, board.height() * 2 - 1);
+    let mut grid: Vec<Vec<(String, Option<Color>)>> =
+        vec![vec![(" ".to_string(), None); width]; height];
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            let symbol = board.board[board.index((x as i32, y as i32))].0;
+            grid[y * 2][x * 2] = (symbol.to_string(), symbol.node_color());
+        }
+    }
+    for &(pos, direction, color) in segments {
+        let (start, direction_inner) = direction.store(pos);
+        let (x, y) = (start.0 as usize * 2, start.1 as usize * 2);
+        // only one of the two crossing beveled edges can ever be placed,
+        // so they share the gap between the 4 nodes
+        let (x, y, glyph) = match Direction::from(direction_inner) {
+            Direction::Right => (x + 1, y, "─"),
+            Direction::Down => (x, y + 1, "│"),
+            Direction::DownRight => (x + 1, y + 1, "╲"),
+            _ => (x - 1, y + 1, "╱"),
+        };
+        grid[y][x] = (glyph.to_string(), Some(color));
+    }
+
+    let mut output = String::new();
+    for (y, row) in grid.into_iter().enumerate() {
+        for (x, (glyph, color)) in row.into_iter().enumerate() {
+            let highlighted =
+                highlight.is_some_and(|(hx, hy)| (hx * 2, hy * 2) == (x as i32, y as i32));
+            if ansi && highlighted {
+                output.push_str(REVERSE);
+            }
+            match color {
+                Some(color) if ansi => {
+                    output.push_str(&color.ansi());
+                    output.push_str(&glyph);
+                    output.push_str(RESET);
+                }
+                _ if ansi && highlighted => {
+                    output.push_str(&glyph);
+                    output.push_str(RESET);
+                }
+                _ => output.push_str(&glyph),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Board, Color};
+
+    #[test]
+    fn render_draws_the_solution_on_the_grid() {
+        let mut board = Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap();
+        let solution = board.solve().unwrap();
+        let expected = concat!(
+            "R─2─B\n",
+            " ╱ ╲ \n",
+            "2─G r\n",
+            "│╲  │\n",
+            "g b R\n",
+            " ╲ ╲ \n",
+            ". G B\n",
+        );
+        assert_eq!(solution.render(&board), expected);
+        assert_eq!(board.render(), expected);
+    }
+
+    #[test]
+    fn render_ansi_colors_the_lines() {
+        let mut board = Board::parse("RrR").unwrap();
+        let solution = board.solve().unwrap();
+        assert_eq!(solution.render(&board), "R─r─R\n");
+        let ansi = solution.render_ansi(&board);
+        assert!(ansi.contains(&Color::Red.ansi()));
+        assert_eq!(strip_ansi(&ansi), "R─r─R\n");
+    }
+
+    fn strip_ansi(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+}
@@ -0,0 +1,75 @@
+use log::info;
+
+use crate::clock::Instant;
+use crate::solver::first_moves;
+use crate::{Board, DirectionOrder, Stats};
+
+// search for the first solution with directions in random order, giving up
+// after `attempts` line attempts and starting over on a fresh copy of the
+// board with the next seed and twice the attempts, leaving the solution on
+// `board`
+//
+// the budget doubles so that a search eventually ends on its own, which is
+// needed to tell that there is no solution; the timeout and `max_attempts`
+// of the options bound all the searches together
+pub(crate) fn solve_restarts(board: &mut Board, attempts: u64) -> bool {
+    let options = board.options.clone();
+    let mut seed = match options.direction_order {
+        DirectionOrder::Random(seed) => seed,
+        _ => 0,
+    };
+    let now = Instant::now();
+    let mut total = board.stats.clone();
+    let mut budget = attempts.max(1);
+    loop {
+        let timeout = match options.timeout {
+            Some(timeout) if now.elapsed() >= timeout => {
+                total.timed_out = true;
+                break;
+            }
+            Some(timeout) => Some(timeout - now.elapsed()),
+            None => None,
+        };
+        let spent = total.add_line_calls;
+        let max_attempts = match options.max_attempts {
+            Some(max) if spent >= max => {
+                total.timed_out = true;
+                total.out_of_budget = true;
+                break;
+            }
+            Some(max) => Some(budget.min(max - spent)),
+            None => Some(budget),
+        };
+
+        let mut attempt = board.clone();
+        attempt.stats = Stats::default();
+        attempt.options.direction_order = DirectionOrder::Random(seed);
+        attempt.options.timeout = timeout;
+        attempt.options.max_attempts = max_attempts;
+        attempt.options.restart_attempts = None;
+        let solved = attempt.search_first(first_moves(&attempt), &mut ());
+        let gave_up = attempt.stats.out_of_budget;
+        if gave_up {
+            // out of the budget of this search only, unless the next round
+            // finds the total used up
+            attempt.stats.out_of_budget = false;
+            attempt.stats.timed_out = false;
+        }
+        total.merge(&attempt.stats);
+        if solved {
+            attempt.options = options;
+            *board = attempt;
+            board.stats = total;
+            return true;
+        }
+        if !gave_up {
+            // the whole tree was searched, or the timeout hit
+            break;
+        }
+        seed = seed.wrapping_add(1);
+        info!("restarting after {} attempts with seed {}", budget, seed);
+        budget = budget.saturating_mul(2);
+    }
+    board.stats = total;
+    false
+}
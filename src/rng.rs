@@ -0,0 +1,46 @@
+// A small splitmix64 generator, enough to draw boards and shuffle moves
+// reproducibly
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // a number in `0..n`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+impl Rng {
+    // the text of a random `width` x `height` board with red and blue
+    // endpoints, red and blue nodes, white nodes of 1 or 2 and empty cells,
+    // solvable or not
+    pub(crate) fn board_text(&mut self, width: usize, height: usize) -> String {
+        let mut cells = (0..width * height)
+            .map(|_| ['r', 'r', 'b', 'b', '1', '2', '.'][self.below(7)])
+            .collect::<Vec<_>>();
+        let mut order = (0..cells.len()).collect::<Vec<_>>();
+        self.shuffle(&mut order);
+        for (&i, end) in order.iter().zip(['R', 'R', 'B', 'B']) {
+            cells[i] = end;
+        }
+        cells
+            .chunks(width)
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
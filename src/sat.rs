@@ -0,0 +1,422 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::dlx::draw;
+use crate::solver::{cancelled, out_of_budget, with_timeout};
+use crate::{Board, Color, Direction, DirectionInner, Stats, Symbol};
+
+// A line, by the index of the node storing it and its direction
+type Line = (usize, DirectionInner);
+
+// A literal, its variable shifted left once with the low bit set when negated
+type Lit = usize;
+
+fn positive(var: usize) -> Lit {
+    var << 1
+}
+
+fn negative(var: usize) -> Lit {
+    var << 1 | 1
+}
+
+// A formula in conjunctive normal form
+#[derive(Default)]
+struct Cnf {
+    vars: usize,
+    clauses: Vec<Vec<Lit>>,
+}
+
+impl Cnf {
+    fn var(&mut self) -> usize {
+        self.vars += 1;
+        self.vars - 1
+    }
+
+    // exactly `k` of `lits` true, listing the subsets of at most 8 of them
+    fn exactly(&mut self, lits: &[Lit], k: usize) {
+        let n = lits.len();
+        if k > n {
+            self.clauses.push(Vec::new());
+            return;
+        }
+        for mask in 0u32..1 << n {
+            let chosen = || (0..n).filter(move |i| mask >> i & 1 == 1);
+            let ones = mask.count_ones() as usize;
+            // any k + 1 have a false one, any n - k + 1 a true one
+            if ones == k + 1 {
+                self.clauses.push(chosen().map(|i| lits[i] ^ 1).collect());
+            }
+            if ones == n - k + 1 {
+                self.clauses.push(chosen().map(|i| lits[i]).collect());
+            }
+        }
+    }
+
+    // an even number of `lits` true, ruling out each odd assignment
+    fn even(&mut self, lits: &[Lit]) {
+        let n = lits.len();
+        for mask in (0u32..1 << n).filter(|mask| mask.count_ones() % 2 == 1) {
+            self.clauses
+                .push((0..n).map(|i| lits[i] ^ (mask >> i & 1) as usize).collect());
+        }
+    }
+
+    // DPLL with unit propagation on two watched literals per clause, trying
+    // variables false first in their order, calling `tried` at each decision
+    //
+    // return the value of every variable, or None if there is no model or
+    // `tried` stopped the search
+    fn solve(&self, tried: &mut dyn FnMut() -> bool) -> Option<Vec<bool>> {
+        let mut clauses = self.clauses.clone();
+        let mut value: Vec<Option<bool>> = vec![None; self.vars];
+        let mut watches: Vec<Vec<usize>> = vec![Vec::new(); 2 * self.vars];
+        let mut trail: Vec<Lit> = Vec::new();
+        // the trail length at each decision, and whether it was flipped
+        let mut decisions: Vec<(usize, bool)> = Vec::new();
+        let truth = |value: &[Option<bool>], lit: Lit| value[lit >> 1].map(|v| v == (lit & 1 == 0));
+
+        for (c, clause) in clauses.iter().enumerate() {
+            match clause[..] {
+                [] => return None,
+                [lit] => match truth(&value, lit) {
+                    Some(false) => return None,
+                    Some(true) => {}
+                    None => {
+                        value[lit >> 1] = Some(lit & 1 == 0);
+                        trail.push(lit);
+                    }
+                },
+                [a, b, ..] => {
+                    watches[a].push(c);
+                    watches[b].push(c);
+                }
+            }
+        }
+
+        let mut head = 0;
+        loop {
+            // propagate the literals set since the last conflict or decision
+            let mut conflict = false;
+            while head < trail.len() && !conflict {
+                let falsified = trail[head] ^ 1;
+                head += 1;
+                let mut watching = std::mem::take(&mut watches[falsified]);
+                let mut i = 0;
+                while i < watching.len() {
+                    let clause = &mut clauses[watching[i]];
+                    if clause[0] == falsified {
+                        clause.swap(0, 1);
+                    }
+                    if truth(&value, clause[0]) == Some(true) {
+                        i += 1;
+                        continue;
+                    }
+                    if let Some(k) =
+                        (2..clause.len()).find(|&k| truth(&value, clause[k]) != Some(false))
+                    {
+                        clause.swap(1, k);
+                        watches[clause[1]].push(watching.swap_remove(i));
+                        continue;
+                    }
+                    match truth(&value, clause[0]) {
+                        None => {
+                            value[clause[0] >> 1] = Some(clause[0] & 1 == 0);
+                            trail.push(clause[0]);
+                        }
+                        _ => {
+                            conflict = true;
+                            break;
+                        }
+                    }
+                    i += 1;
+                }
+                watches[falsified].extend(watching);
+            }
+
+            if conflict {
+                // flip the last decision not flipped yet
+                loop {
+                    let (start, flipped) = decisions.pop()?;
+                    let decided = trail[start];
+                    for &lit in &trail[start..] {
+                        value[lit >> 1] = None;
+                    }
+                    trail.truncate(start);
+                    if !flipped {
+                        decisions.push((start, true));
+                        value[decided >> 1] = Some(decided & 1 == 1);
+                        trail.push(decided ^ 1);
+                        break;
+                    }
+                }
+                head = trail.len() - 1;
+                continue;
+            }
+
+            let Some(var) = value.iter().position(Option::is_none) else {
+                return Some(value.into_iter().map(|v| v.unwrap()).collect());
+            };
+            if tried() {
+                return None;
+            }
+            decisions.push((trail.len(), false));
+            value[var] = Some(false);
+            trail.push(negative(var));
+        }
+    }
+}
+
+// The formula of a board: a variable for each color a line can have, and
+// one for whether it is drawn at all
+struct Encoding {
+    cnf: Cnf,
+    // the color variables of each line, by its stored position
+    lines: Vec<(Line, Vec<(Color, usize)>)>,
+}
+
+impl Encoding {
+    fn new(board: &Board) -> Self {
+        // the colors that can touch each node
+        let colors = (0..board.board.len())
+            .map(|i| match board.board[i].0 {
+                Symbol::Node(color) | Symbol::End(color) => vec![color],
+                Symbol::White(_) => board
+                    .colors()
+                    .iter()
+                    .copied()
+                    .filter(|&c| board.allowed[i].contains(c))
+                    .collect(),
+                Symbol::Empty => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut cnf = Cnf::default();
+        let mut lines = Vec::new();
+        let mut line_var = HashMap::new();
+        for index in 0..board.board.len() {
+            let pos = board.pos(index);
+            for inner in (0..4).map(DirectionInner::from) {
+                let next = Direction::from(inner).apply_offset(pos);
+                if !board.in_bounds(next) {
+                    continue;
+                }
+                let next_index = board.index(next);
+                let vars = colors[index]
+                    .iter()
+                    .filter(|c| colors[next_index].contains(c))
+                    .map(|&c| (c, cnf.var()))
+                    .collect::<Vec<_>>();
+                if vars.is_empty() {
+                    continue;
+                }
+                let line = cnf.var();
+                // drawn with exactly one color, or not at all
+                cnf.clauses.push(
+                    std::iter::once(negative(line))
+                        .chain(vars.iter().map(|&(_, var)| positive(var)))
+                        .collect(),
+                );
+                for (i, &(_, var)) in vars.iter().enumerate() {
+                    cnf.clauses.push(vec![negative(var), positive(line)]);
+                    for &(_, other) in &vars[..i] {
+                        cnf.clauses.push(vec![negative(var), negative(other)]);
+                    }
+                }
+                if let Some(color) = board.lines[index][inner as usize] {
+                    match vars.iter().find(|&&(c, _)| c == color) {
+                        Some(&(_, var)) => cnf.clauses.push(vec![positive(var)]),
+                        None => cnf.clauses.push(Vec::new()),
+                    }
+                }
+                line_var.insert((index, inner), line);
+                lines.push(((index, inner), vars));
+            }
+        }
+
+        // crossing diagonals
+        for &((index, inner), _) in &lines {
+            let (x, y) = board.pos(index);
+            if inner != DirectionInner::DownRight || !board.in_bounds((x + 1, y)) {
+                continue;
+            }
+            let other = (board.index((x + 1, y)), DirectionInner::DownLeft);
+            if let Some(&crossing) = line_var.get(&other) {
+                let line = line_var[&(index, inner)];
+                cnf.clauses.push(vec![negative(line), negative(crossing)]);
+            }
+        }
+
+        // the lines of each node, and of each of its colors
+        let mut touching = vec![Vec::new(); board.board.len()];
+        for (i, ((index, inner), _)) in lines.iter().enumerate() {
+            let next = Direction::from(*inner).apply_offset(board.pos(*index));
+            touching[*index].push(i);
+            touching[board.index(next)].push(i);
+        }
+        for (index, touching) in touching.iter().enumerate() {
+            let line_vars = |color: Color| {
+                touching
+                    .iter()
+                    .filter_map(|&i| lines[i].1.iter().find(|&&(c, _)| c == color))
+                    .map(|&(_, var)| positive(var))
+                    .collect::<Vec<_>>()
+            };
+            match board.board[index].0 {
+                Symbol::Node(color) => cnf.exactly(&line_vars(color), 2),
+                Symbol::End(color) => cnf.exactly(&line_vars(color), 1),
+                Symbol::White(n) => {
+                    let all = touching
+                        .iter()
+                        .map(|&i| positive(line_var[&lines[i].0]))
+                        .collect::<Vec<_>>();
+                    cnf.exactly(&all, 2 * usize::from(n));
+                    for &color in &colors[index] {
+                        cnf.even(&line_vars(color));
+                    }
+                }
+                Symbol::Empty => {}
+            }
+        }
+        Encoding { cnf, lines }
+    }
+
+    // the lines drawn in `model`, with their color
+    fn drawn(&self, model: &[bool]) -> Vec<(usize, DirectionInner, Color)> {
+        self.lines
+            .iter()
+            .filter_map(|&((index, inner), ref vars)| {
+                vars.iter()
+                    .find(|&&(_, var)| model[var])
+                    .map(|&(color, _)| (index, inner, color))
+            })
+            .collect()
+    }
+
+    // a clause ruling out each loop of `lines` apart from the path of its
+    // color, unless another line of the color touches it: the loop has no
+    // endpoint, as they only have one line
+    fn block_loops(
+        &self,
+        board: &Board,
+        lines: &[(usize, DirectionInner, Color)],
+    ) -> Vec<Vec<Lit>> {
+        let ends = |(index, inner): Line| {
+            let next = Direction::from(inner).apply_offset(board.pos(index));
+            [index, board.index(next)]
+        };
+        let mut clauses = Vec::new();
+        let mut seen = vec![false; lines.len()];
+        for first in 0..lines.len() {
+            if seen[first] {
+                continue;
+            }
+            let color = lines[first].2;
+            // the lines of `color` connected to the first one
+            let mut loop_lines = vec![first];
+            let mut nodes = ends((lines[first].0, lines[first].1)).to_vec();
+            seen[first] = true;
+            let mut grown = true;
+            while grown {
+                grown = false;
+                for (i, &(index, inner, c)) in lines.iter().enumerate() {
+                    let touches = ends((index, inner)).iter().any(|n| nodes.contains(n));
+                    if !seen[i] && c == color && touches {
+                        seen[i] = true;
+                        loop_lines.push(i);
+                        nodes.extend(ends((index, inner)));
+                        grown = true;
+                    }
+                }
+            }
+            if nodes
+                .iter()
+                .any(|&n| board.board[n].0 == Symbol::color_end(color))
+            {
+                continue;
+            }
+            let var = |key| {
+                self.lines
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .and_then(|(_, vars)| vars.iter().find(|&&(c, _)| c == color))
+                    .map(|&(_, var)| var)
+            };
+            let mut clause = Vec::new();
+            for &(index, inner, _) in loop_lines.iter().map(|&i| &lines[i]) {
+                clause.extend(var((index, inner)).map(negative));
+            }
+            for ((index, inner), _) in &self.lines {
+                let key = (*index, *inner);
+                let inside = loop_lines.iter().any(|&i| (lines[i].0, lines[i].1) == key);
+                if !inside && ends(key).iter().any(|n| nodes.contains(n)) {
+                    clause.extend(var(key).map(positive));
+                }
+            }
+            clauses.push(clause);
+        }
+        clauses
+    }
+}
+
+// search the first solution of `board` with a SAT solver, leaving it on the
+// board
+//
+// the formula only constrains each node and crossing, so the models whose
+// lines leave a loop apart from the path of its color are ruled out one by
+// one and the formula solved again; every decision counts as a line attempt
+// in the stats, so the timeout and `max_attempts` bound this search like the
+// backtracking one
+pub(crate) fn solve_sat(board: &mut Board) -> bool {
+    let mut encoding = Encoding::new(board);
+    info!(
+        "formula of {} variables and {} clauses",
+        encoding.cnf.vars,
+        encoding.cnf.clauses.len()
+    );
+    let cancel = [AtomicBool::new(false)];
+    let timeout = board.options.timeout;
+    let mut start = board.clone();
+    start.stats = Stats::default();
+    let (solved, timed_out) = with_timeout(timeout, &cancel, || loop {
+        let model = encoding.cnf.solve(&mut || {
+            board.stats.add_line_calls += 1;
+            cancel[0].load(Ordering::Relaxed) || cancelled(board) || out_of_budget(board)
+        })?;
+        let lines = encoding.drawn(&model);
+        let loops = encoding.block_loops(board, &lines);
+        if loops.is_empty() {
+            let mut drawn = start.clone();
+            if draw(&mut drawn, &lines) {
+                return Some(drawn);
+            }
+            // rule out this model alone
+            let clause = (0..model.len())
+                .map(|var| {
+                    if model[var] {
+                        negative(var)
+                    } else {
+                        positive(var)
+                    }
+                })
+                .collect();
+            encoding.cnf.clauses.push(clause);
+        } else {
+            debug!("model with {} loops apart from the paths", loops.len());
+            encoding.cnf.clauses.extend(loops);
+        }
+    });
+    match solved {
+        Some(drawn) => {
+            let mut stats = std::mem::take(&mut board.stats);
+            stats.merge(&drawn.stats);
+            *board = drawn;
+            board.stats = stats;
+            true
+        }
+        None => {
+            board.stats.timed_out |= timed_out;
+            false
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use crate::clock::Instant;
+use crate::parse::{color_named, direction_named, json_point};
+use crate::solver::{directions_from, Frame};
+use crate::{Board, Color, Direction, Json, ParseError, Point, SearchObserver, Solution, Symbol};
+
+// A frame of the search as saved in a `SearchSnapshot`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SavedFrame {
+    // the path of `color` started at the endpoint `start`, or continued from
+    // its fixed lines without it
+    Color {
+        color: Color,
+        start: Option<Point>,
+    },
+    // the head of the path of `color` at `point`, with its moves, how many
+    // of them were tried and the move taken that is on the board
+    Head {
+        point: Point,
+        color: Color,
+        directions: Vec<Direction>,
+        tried: usize,
+        line: Option<Direction>,
+    },
+}
+
+// The state of a depth first search for the first solution, to save it and
+// resume it later with `Board::resume_observed`, see
+// `SearchObserver::on_snapshot`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSnapshot {
+    // the puzzle with its lines, see `Board::state_to_json`
+    state: Json,
+    // the colors in the order they are solved
+    colors: Vec<Color>,
+    stack: Vec<SavedFrame>,
+    // the depth of the first head, whose moves may be limited
+    restricted: Option<usize>,
+}
+
+// Why a snapshot cannot be resumed on a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeError {
+    // it is the search of another puzzle, or of other given lines
+    OtherPuzzle,
+    // its lines and the steps of its search do not fit together
+    Inconsistent,
+}
+
+impl std::fmt::Display for ResumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResumeError::OtherPuzzle => write!(f, "the snapshot is the search of another puzzle"),
+            ResumeError::Inconsistent => write!(f, "the snapshot is inconsistent"),
+        }
+    }
+}
+
+impl std::error::Error for ResumeError {}
+
+impl SearchSnapshot {
+    pub(crate) fn new(board: &Board, stack: &[Frame], restricted: Option<usize>) -> Self {
+        let stack = stack
+            .iter()
+            .map(|frame| match frame {
+                Frame::Color { color, start, .. } => SavedFrame::Color {
+                    color: *color,
+                    start: start.map(|index| board.pos(index)),
+                },
+                Frame::Head {
+                    point,
+                    color,
+                    directions,
+                    tried,
+                    line,
+                } => SavedFrame::Head {
+                    point: *point,
+                    color: *color,
+                    directions: directions.clone(),
+                    tried: *tried,
+                    line: *line,
+                },
+            })
+            .collect();
+        SearchSnapshot {
+            state: board.state_to_json(),
+            colors: board.colors.clone(),
+            stack,
+            restricted,
+        }
+    }
+
+    // `{"board": .., "colors": ["Red", ..], "stack": [..], "restricted": ..}`
+    // with the board as `Board::state_to_json` writes it, and the steps of
+    // the search from the first color, either `{"color": "Red", "start":
+    // [x, y]}` or `{"color": "Red", "head": [x, y], "directions": ["Right",
+    // ..], "tried": 1, "line": "Right"}`
+    pub fn to_json(&self) -> Json {
+        let name = |direction: Direction| Json::from(direction.to_string());
+        let stack = self.stack.iter().map(|frame| match frame {
+            SavedFrame::Color { color, start } => Json::object([
+                ("color", color.to_string().into()),
+                ("start", start.map_or(Json::Null, Json::point)),
+            ]),
+            SavedFrame::Head {
+                point,
+                color,
+                directions,
+                tried,
+                line,
+            } => Json::object([
+                ("color", color.to_string().into()),
+                ("head", Json::point(*point)),
+                (
+                    "directions",
+                    Json::Array(directions.iter().copied().map(name).collect()),
+                ),
+                ("tried", (*tried).into()),
+                ("line", line.map_or(Json::Null, name)),
+            ]),
+        });
+        Json::object([
+            ("board", self.state.clone()),
+            (
+                "colors",
+                Json::Array(self.colors.iter().map(|c| c.to_string().into()).collect()),
+            ),
+            ("stack", Json::Array(stack.collect())),
+            ("restricted", self.restricted.map_or(Json::Null, Json::from)),
+        ])
+    }
+
+    // read a snapshot written by `to_json`
+    pub fn from_json(json: &Json) -> Result<SearchSnapshot, ParseError> {
+        let invalid = |field: String| ParseError::InvalidSnapshot { field };
+        let color = |json: Option<&Json>, field: String| match json {
+            Some(Json::String(name)) => color_named(name).ok_or_else(|| invalid(field)),
+            _ => Err(invalid(field)),
+        };
+        let direction = |json: &Json, field: String| match json {
+            Json::String(name) => direction_named(name).ok_or_else(|| invalid(field)),
+            _ => Err(invalid(field)),
+        };
+        let count = |json: Option<&Json>, field: String| match json {
+            Some(&Json::Number(n)) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            _ => Err(invalid(field)),
+        };
+        let Some(state) = json.get("board") else {
+            return Err(invalid("board".into()));
+        };
+        let Some(Json::Array(colors)) = json.get("colors") else {
+            return Err(invalid("colors".into()));
+        };
+        let Some(Json::Array(stack)) = json.get("stack") else {
+            return Err(invalid("stack".into()));
+        };
+        let colors = colors
+            .iter()
+            .enumerate()
+            .map(|(i, c)| color(Some(c), format!("colors[{}]", i)))
+            .collect::<Result<_, _>>()?;
+        let stack = stack
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let field = |key: &str| format!("stack[{}].{}", i, key);
+                let color = color(frame.get("color"), field("color"))?;
+                let Some(head) = frame.get("head") else {
+                    let start = match frame.get("start") {
+                        None | Some(Json::Null) => None,
+                        Some(start) => {
+                            Some(json_point(start).ok_or_else(|| invalid(field("start")))?)
+                        }
+                    };
+                    return Ok(SavedFrame::Color { color, start });
+                };
+                let Some(Json::Array(directions)) = frame.get("directions") else {
+                    return Err(invalid(field("directions")));
+                };
+                Ok(SavedFrame::Head {
+                    point: json_point(head).ok_or_else(|| invalid(field("head")))?,
+                    color,
+                    directions: directions
+                        .iter()
+                        .enumerate()
+                        .map(|(j, d)| direction(d, format!("{}[{}]", field("directions"), j)))
+                        .collect::<Result<_, _>>()?,
+                    tried: count(frame.get("tried"), field("tried"))?,
+                    line: match frame.get("line") {
+                        None | Some(Json::Null) => None,
+                        Some(line) => Some(direction(line, field("line"))?),
+                    },
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        let restricted = match json.get("restricted") {
+            None | Some(Json::Null) => None,
+            restricted => Some(count(restricted, "restricted".into())?),
+        };
+        Ok(SearchSnapshot {
+            state: state.clone(),
+            colors,
+            stack,
+            restricted,
+        })
+    }
+}
+
+impl Board {
+    // go on with the search for the first solution saved in `snapshot`, on
+    // this board holding the same puzzle and given lines as the board it was
+    // taken from, telling `observer` as `solve_observed` does
+    //
+    // the search keeps its options, except for the order of the colors, and
+    // its statistics only count the work done since; the dead ends it had
+    // seen are forgotten
+    pub fn resume_observed(
+        &mut self,
+        snapshot: &SearchSnapshot,
+        observer: &mut impl SearchObserver,
+    ) -> Result<Option<Solution>, ResumeError> {
+        let saved = Board::from_json(&snapshot.state).map_err(|_| ResumeError::Inconsistent)?;
+        if saved.to_json() != self.to_json() || saved.fixed_lines() != self.export_lines() {
+            return Err(ResumeError::OtherPuzzle);
+        }
+        let (mut colors, mut saved_colors) = (self.colors.clone(), snapshot.colors.clone());
+        colors.sort();
+        saved_colors.sort();
+        if colors != saved_colors {
+            return Err(ResumeError::Inconsistent);
+        }
+
+        // draw the lines again the way the search did, each path from the
+        // start of its color frame, the colors in their order and a path only
+        // followed by the next once it reaches its other endpoint
+        let mut board = self.clone();
+        let mut stack = Vec::with_capacity(snapshot.stack.len());
+        // the color of the path followed, its place in the order and where
+        // its next head is, None once the top head is met
+        let mut path: Option<(Color, usize, Option<Point>)> = None;
+        for frame in &snapshot.stack {
+            match *frame {
+                SavedFrame::Color { color, start } => {
+                    let order = snapshot.colors.iter().position(|&c| c == color);
+                    let follows = match (path, order) {
+                        (_, None) => false,
+                        (None, Some(_)) => true,
+                        (Some((previous, previous_order, end)), Some(order)) => {
+                            order > previous_order
+                                && end.is_some_and(|end| {
+                                    board.symbol(end) == Some(Symbol::color_end(previous))
+                                })
+                        }
+                    };
+                    let head = start.or_else(|| self.fixed_head(color));
+                    let (Some(order), true, Some(_)) = (order, follows, head) else {
+                        return Err(ResumeError::Inconsistent);
+                    };
+                    path = Some((color, order, head));
+                    let start = match start {
+                        Some(pos) if board.in_bounds(pos) => {
+                            if board.symbol(pos) != Some(Symbol::color_end(color)) {
+                                return Err(ResumeError::Inconsistent);
+                            }
+                            let index = board.index(pos);
+                            board.board[index].1 += 1;
+                            Some(index)
+                        }
+                        Some(_) => return Err(ResumeError::Inconsistent),
+                        None => None,
+                    };
+                    stack.push(Frame::Color {
+                        color,
+                        start,
+                        since: Instant::now(),
+                    });
+                }
+                SavedFrame::Head {
+                    point,
+                    color,
+                    ref directions,
+                    tried,
+                    line,
+                } => {
+                    let Some((path_color, order, Some(head))) = path else {
+                        return Err(ResumeError::Inconsistent);
+                    };
+                    // the moves are all 8 in some order, only the head the
+                    // first moves restrict may have fewer, and the line drawn
+                    // is the last move tried, if the head still holds it
+                    let mut moves = directions.clone();
+                    moves.sort();
+                    moves.dedup();
+                    let mut all = directions_from(&board, point, color);
+                    all.sort();
+                    let restricted = snapshot.restricted == Some(stack.len() + 1);
+                    let moves_match = moves.len() == directions.len()
+                        && if restricted {
+                            moves.iter().all(|d| all.contains(d))
+                        } else {
+                            moves == all
+                        };
+                    if color != path_color
+                        || point != head
+                        || tried > directions.len()
+                        || !moves_match
+                        || line
+                            .is_some_and(|d| tried.checked_sub(1).map(|i| directions[i]) != Some(d))
+                    {
+                        return Err(ResumeError::Inconsistent);
+                    }
+                    path = Some((color, order, line.map(|d| d.apply_offset(point))));
+                    if let Some(direction) = line {
+                        board
+                            .add_line(point, direction, color)
+                            .map_err(|_| ResumeError::Inconsistent)?;
+                    }
+                    stack.push(Frame::Head {
+                        point,
+                        color,
+                        directions: directions.clone(),
+                        tried,
+                        line,
+                    });
+                }
+            }
+        }
+        if board.result != saved.result
+            || snapshot.restricted.is_some_and(|depth| depth > stack.len())
+        {
+            return Err(ResumeError::Inconsistent);
+        }
+        board.colors = snapshot.colors.clone();
+        board.stats = std::mem::take(&mut self.stats);
+        *self = board;
+        Ok(self.resume_first(stack, snapshot.restricted, observer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SolveOptions;
+
+    // a 5x5 board with three colors and eight white nodes
+    const WHITES: &str = "bb2R.\nb222R\nB32Br\nG222.\nGgggg";
+
+    // Keeps the snapshot taken when the search gives up
+    struct Keep(Option<SearchSnapshot>);
+
+    impl SearchObserver for Keep {
+        fn wants_snapshot(&mut self, stopping: bool) -> bool {
+            stopping
+        }
+
+        fn on_snapshot(&mut self, snapshot: SearchSnapshot) {
+            self.0 = Some(snapshot);
+        }
+    }
+
+    // the puzzle in `text` and its search given up after `attempts` lines
+    fn stopped(text: &str, attempts: u64) -> (Board, SearchSnapshot) {
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            threads: 1,
+            max_attempts: Some(attempts),
+            ..SolveOptions::default()
+        });
+        let mut keep = Keep(None);
+        assert!(board.solve_observed(&mut keep).is_none());
+        (Board::parse(text).unwrap(), keep.0.unwrap())
+    }
+
+    fn color_frames(snapshot: &SearchSnapshot) -> Vec<usize> {
+        (0..snapshot.stack.len())
+            .filter(|&i| matches!(snapshot.stack[i], SavedFrame::Color { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn a_snapshot_resumes_to_the_solution() {
+        let (mut board, snapshot) = stopped(WHITES, 640);
+        let mut fresh = Board::parse(WHITES).unwrap();
+        fresh.set_options(SolveOptions {
+            threads: 1,
+            ..SolveOptions::default()
+        });
+        let solution = board.resume_observed(&snapshot, &mut ()).unwrap();
+        assert_eq!(solution, fresh.solve());
+    }
+
+    #[test]
+    fn a_snapshot_missing_a_color_frame_is_inconsistent() {
+        let (board, snapshot) = stopped(WHITES, 640);
+        let frames = color_frames(&snapshot);
+        assert_eq!(frames.len(), 3);
+        for i in frames {
+            let mut broken = snapshot.clone();
+            broken.stack.remove(i);
+            assert_eq!(
+                board.clone().resume_observed(&broken, &mut ()),
+                Err(ResumeError::Inconsistent)
+            );
+        }
+    }
+
+    #[test]
+    fn a_head_tried_past_its_line_is_inconsistent() {
+        let (board, snapshot) = stopped(WHITES, 640);
+        let heads = (0..snapshot.stack.len())
+            .filter(|&i| matches!(snapshot.stack[i], SavedFrame::Head { line: Some(_), .. }))
+            .collect::<Vec<_>>();
+        assert!(!heads.is_empty());
+        for i in heads {
+            for change in [-1, 1] {
+                let mut broken = snapshot.clone();
+                let SavedFrame::Head { tried, .. } = &mut broken.stack[i] else {
+                    unreachable!()
+                };
+                *tried = tried.wrapping_add_signed(change);
+                assert_eq!(
+                    board.clone().resume_observed(&broken, &mut ()),
+                    Err(ResumeError::Inconsistent)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_head_missing_a_move_is_inconsistent() {
+        let (mut board, mut snapshot) = stopped(WHITES, 640);
+        let Some(SavedFrame::Head {
+            directions, tried, ..
+        }) = snapshot.stack.last_mut()
+        else {
+            panic!("the search stopped on a color frame");
+        };
+        let last = directions.pop();
+        assert!(last.is_some() && *tried <= directions.len());
+        assert_eq!(
+            board.resume_observed(&snapshot, &mut ()),
+            Err(ResumeError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn a_malformed_snapshot_names_its_field() {
+        let (_, snapshot) = stopped(WHITES, 640);
+        let head = color_frames(&snapshot)[0] + 1;
+        let Json::Object(mut fields) = snapshot.to_json() else {
+            panic!("a snapshot is a JSON object");
+        };
+        let Some((_, Json::Array(stack))) = fields.iter_mut().find(|(key, _)| key == "stack")
+        else {
+            panic!("a snapshot has a stack");
+        };
+        let Json::Object(frame) = &mut stack[head] else {
+            panic!("a frame is a JSON object");
+        };
+        frame.retain(|(key, _)| key != "tried");
+        assert_eq!(
+            SearchSnapshot::from_json(&Json::Object(fields)).unwrap_err(),
+            ParseError::InvalidSnapshot {
+                field: format!("stack[{}].tried", head),
+            }
+        );
+    }
+}
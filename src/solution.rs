@@ -0,0 +1,203 @@
+use crate::{node_order, Color, Direction, DirectionInner, Point};
+
+// Lines of a solved board, in the order the solver placed them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    segments: Vec<(Point, Direction, Color)>,
+    // number of segments at the start that were fixed before solving
+    given: usize,
+}
+
+impl Solution {
+    pub(crate) fn new(segments: Vec<(Point, Direction, Color)>, given: usize) -> Self {
+        Solution { segments, given }
+    }
+
+    pub fn segments(&self) -> &[(Point, Direction, Color)] {
+        &self.segments
+    }
+
+    // the segments fixed on the board before solving
+    pub fn given(&self) -> &[(Point, Direction, Color)] {
+        &self.segments[..self.given]
+    }
+
+    // the segments the solver added to the fixed ones
+    pub fn added(&self) -> &[(Point, Direction, Color)] {
+        &self.segments[self.given..]
+    }
+
+    // the first segment to draw after the fixed ones, on the color given last
+    // when it is not finished so that a player can go on with that path
+    pub fn next_segment(&self) -> Option<(Point, Direction, Color)> {
+        let last = self.given().last().map(|s| s.2);
+        let added = self.added();
+        added
+            .iter()
+            .find(|s| Some(s.2) == last)
+            .or(added.first())
+            .copied()
+    }
+
+    pub fn segments_for(&self, color: Color) -> impl Iterator<Item = &(Point, Direction, Color)> {
+        self.segments.iter().filter(move |s| s.2 == color)
+    }
+
+    // the nodes visited by each color, in the order of the colors, from one
+    // endpoint to the other
+    pub fn paths(&self) -> Vec<(Color, Vec<Point>)> {
+        paths_of(&self.segments, &[])
+    }
+
+    // the nodes visited by `color` from one endpoint to the other, or None
+    // if the color has no segment
+    pub fn path_for(&self, color: Color) -> Option<Vec<Point>> {
+        let segments = self.segments_for(color).copied().collect::<Vec<_>>();
+        paths_of(&segments, &[]).pop().map(|(_, path)| path)
+    }
+
+    // the nodes visited by the fixed segments of each color
+    pub fn given_paths(&self) -> Vec<(Color, Vec<Point>)> {
+        paths_of(self.given(), &[])
+    }
+
+    // the nodes visited by the added segments of each color, from where its
+    // fixed segments stop
+    pub fn added_paths(&self) -> Vec<(Color, Vec<Point>)> {
+        let heads = self
+            .given()
+            .iter()
+            .map(|&(pos, direction, color)| (color, direction.apply_offset(pos)))
+            .collect::<Vec<_>>();
+        paths_of(self.added(), &heads)
+    }
+
+    // the lines in the form they are stored on the board, sorted
+    //
+    // solutions drawing the same lines in another order or direction have
+    // the same normalized form
+    pub fn normalized(&self) -> Vec<(Point, DirectionInner, Color)> {
+        let mut lines = self
+            .segments
+            .iter()
+            .map(|&(pos, direction, color)| {
+                let (pos, direction_inner) = direction.store(pos);
+                (pos, direction_inner, color)
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines
+    }
+}
+
+// chain the segments of each color into the nodes they visit, starting from
+// the last of `heads` of that color if any, else from an endpoint
+fn paths_of(
+    segments: &[(Point, Direction, Color)],
+    heads: &[(Color, Point)],
+) -> Vec<(Color, Vec<Point>)> {
+    let mut colors = segments.iter().map(|s| s.2).collect::<Vec<_>>();
+    colors.sort();
+    colors.dedup();
+    colors
+        .into_iter()
+        .map(|color| {
+            let lines = segments
+                .iter()
+                .filter(|s| s.2 == color)
+                .map(|&(pos, direction, _)| (pos, direction.apply_offset(pos)))
+                .collect::<Vec<_>>();
+            // the endpoints are the only nodes with an odd number of lines
+            let start = heads
+                .iter()
+                .rev()
+                .find(|(c, _)| *c == color)
+                .map(|&(_, head)| head)
+                .or_else(|| {
+                    lines
+                        .iter()
+                        .flat_map(|&(a, b)| [a, b])
+                        .filter(|&p| {
+                            lines.iter().filter(|&&(a, b)| a == p || b == p).count() % 2 == 1
+                        })
+                        .min_by_key(|&p| node_order(p))
+                })
+                .unwrap_or(lines[0].0);
+            (color, chain(start, lines))
+        })
+        .collect()
+}
+
+// walk all `lines` from `start`, in whichever direction they were drawn
+//
+// the lines are taken in their order wherever there is a choice, so lines
+// already in walking order come out unchanged
+pub(crate) fn chain(start: Point, mut lines: Vec<(Point, Point)>) -> Vec<Point> {
+    // Hierholzer's algorithm, a greedy walk could get stuck on a white node
+    // passed several times
+    let mut stack = vec![start];
+    let mut path = Vec::new();
+    while let Some(&current) = stack.last() {
+        match lines
+            .iter()
+            .position(|&(a, b)| a == current || b == current)
+        {
+            Some(i) => {
+                let (a, b) = lines.remove(i);
+                stack.push(if a == current { b } else { a });
+            }
+            None => path.push(stack.pop().unwrap()),
+        }
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Board, Symbol};
+
+    #[test]
+    fn each_colored_node_appears_exactly_once_in_the_paths() {
+        for text in ["R2B\n2Gr\ngbR\n.GB", "Rr.\n.2B\nRbB", "RrrR\nrrrr\nrrrr"] {
+            let mut board = Board::parse(text).unwrap();
+            let paths = board.solve().unwrap().paths();
+            for (index, symbol) in board.symbols().enumerate() {
+                let pos = board.pos(index);
+                let visits = paths
+                    .iter()
+                    .flat_map(|(color, path)| path.iter().map(move |&p| (*color, p)))
+                    .filter(|&(_, p)| p == pos)
+                    .collect::<Vec<_>>();
+                match symbol.node_color() {
+                    Some(color) => assert_eq!(visits, [(color, pos)], "{}", text),
+                    None if symbol == Symbol::Empty => assert!(visits.is_empty()),
+                    None => assert!(!visits.is_empty()),
+                }
+            }
+            for (color, path) in &paths {
+                for end in [path[0], path[path.len() - 1]] {
+                    assert_eq!(board.symbol(end), Some(Symbol::End(*color)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn paths_follow_segments_stored_backwards() {
+        // the middle segment is given from its far end
+        let solution = Solution::new(
+            vec![
+                ((0, 0), Direction::Right, Color::Red),
+                ((2, 0), Direction::Left, Color::Red),
+                ((2, 0), Direction::Right, Color::Red),
+            ],
+            0,
+        );
+        assert_eq!(
+            solution.paths(),
+            [(Color::Red, vec![(0, 0), (1, 0), (2, 0), (3, 0)])]
+        );
+    }
+}
@@ -0,0 +1,960 @@
+use log::{debug, info, trace};
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+use crate::clock::Instant;
+use crate::dlx::solve_dlx;
+use crate::parallel::solve_parallel;
+use crate::restart::solve_restarts;
+use crate::rng::Rng;
+use crate::{
+    node_order, Board, CancellationToken, Cancelled, Color, Direction, Point, SearchObserver,
+    SearchSnapshot, Solution, Stats, Symbol,
+};
+
+// The order in which the moves from a node are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectionOrder {
+    // `Direction` declaration order
+    Fixed,
+    // uncovered nodes of the color first, then white nodes, and the endpoint
+    // last while nodes of the color are left
+    Greedy,
+    // the moves closest to the endpoint the path has to reach first
+    Target,
+    // shuffled with this seed, the same for every visit of a node by a color
+    Random(u64),
+}
+
+// How `solve` searches for a solution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    // depth first search drawing the paths one line at a time
+    Dfs,
+    // dancing links over the lines of every node as an exact cover problem,
+    // on a single thread, ignoring the move ordering and restarts
+    Dlx,
+    // a SAT solver over the colors of every line, on a single thread,
+    // ignoring the move ordering and restarts
+    #[cfg(feature = "sat")]
+    Sat,
+}
+
+// How the search is run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveOptions {
+    pub algorithm: Algorithm,
+    // abandon paths that cut their color off from the nodes left to cover,
+    // or that leave a white node without enough free lines
+    pub prune: bool,
+    // colors to solve first, in this order, before the remaining ones
+    pub color_order: Option<Vec<Color>>,
+    pub direction_order: DirectionOrder,
+    // threads sharing the first moves of `solve`, which finds the same
+    // solution with any number of them
+    pub threads: usize,
+    // give up searching after this long, see `Stats::timed_out`
+    pub timeout: Option<Duration>,
+    // give up searching after this many `add_line` attempts, see
+    // `Stats::out_of_budget`; `solve` then runs on a single thread so that
+    // the outcome is the same on every machine
+    pub max_attempts: Option<u64>,
+    // dead end states `solve` remembers, forgetting them all once full,
+    // 0 to remember none
+    pub transposition_size: usize,
+    // give up the first search of `solve` after this many attempts and start
+    // over with directions in `DirectionOrder::Random` order, a new seed and
+    // twice the attempts each time, until a search ends on its own
+    pub restart_attempts: Option<u64>,
+    // stop searching once this token is cancelled, see `Stats::cancelled`
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            algorithm: Algorithm::Dfs,
+            prune: true,
+            color_order: None,
+            direction_order: DirectionOrder::Fixed,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            timeout: None,
+            max_attempts: None,
+            transposition_size: 1 << 20,
+            restart_attempts: None,
+            cancellation: None,
+        }
+    }
+}
+
+// Called with the board of every solution found, returns whether the search
+// should stop there
+type Found<'a> = dyn FnMut(&Board) -> bool + 'a;
+
+// Whether a board has exactly one solution, with the solutions found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Uniqueness {
+    NoSolution,
+    Unique(Solution),
+    Multiple(Solution, Solution),
+}
+
+impl Board {
+    // the work done by the searches on this board so far
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn set_options(&mut self, options: SolveOptions) {
+        self.options = options;
+        self.colors = self.solve_order();
+    }
+
+    // the colors on the board in the order they are solved
+    //
+    // the color with the fewest nodes constrains the others most, so colors
+    // are ranked by their number of nodes unless `color_order` says otherwise
+    pub(crate) fn solve_order(&self) -> Vec<Color> {
+        let mut colors = Color::iter()
+            .filter(|&c| self.symbols().any(|s| s.node_color() == Some(c)))
+            .collect::<Vec<_>>();
+        colors.sort_by_key(|&c| self.symbols().filter(|s| s.node_color() == Some(c)).count());
+        if let Some(order) = &self.options.color_order {
+            colors.sort_by_key(|c| order.iter().position(|o| o == c).unwrap_or(order.len()));
+        }
+        colors
+    }
+
+    // same as `solve`, giving up with `Err(Cancelled)` as soon as `token` is
+    // cancelled, from another thread
+    pub fn solve_cancellable(
+        &mut self,
+        token: &CancellationToken,
+    ) -> Result<Option<Solution>, Cancelled> {
+        let cancellation = self.options.cancellation.replace(token.clone());
+        let solution = self.solve();
+        self.options.cancellation = cancellation;
+        if self.stats.cancelled {
+            return Err(Cancelled);
+        }
+        Ok(solution)
+    }
+
+    // search a solution, leaving its lines on the board
+    //
+    // the depth first search does not start on a board failing `precheck`
+    pub fn solve(&mut self) -> Option<Solution> {
+        match self.options.algorithm {
+            Algorithm::Dfs if self.precheck().is_err() => return None,
+            Algorithm::Dfs => {}
+            Algorithm::Dlx => {
+                let solved = solve_dlx(self);
+                return self.solution_found(solved);
+            }
+            #[cfg(feature = "sat")]
+            Algorithm::Sat => {
+                let solved = crate::sat::solve_sat(self);
+                return self.solution_found(solved);
+            }
+        }
+        if let Some(attempts) = self.options.restart_attempts {
+            let solved = solve_restarts(self, attempts);
+            return self.solution_found(solved);
+        }
+        let moves = first_moves(self);
+        let solved = if self.options.threads > 1 && self.options.max_attempts.is_none() {
+            solve_parallel(self, moves)
+        } else {
+            self.search_first(moves, &mut ())
+        };
+        self.solution_found(solved)
+    }
+
+    // same as `solve`, telling `observer` about every line drawn and taken
+    // back, always on a single thread
+    //
+    // the depth first search does not start on a board failing `precheck`
+    pub fn solve_observed(&mut self, observer: &mut impl SearchObserver) -> Option<Solution> {
+        if self.precheck().is_err() {
+            return None;
+        }
+        let moves = first_moves(self);
+        let solved = self.search_first(moves, observer);
+        self.solution_found(solved)
+    }
+
+    // search the first solution on this thread
+    pub(crate) fn search_first(
+        &mut self,
+        moves: Option<Vec<Direction>>,
+        observer: &mut impl SearchObserver,
+    ) -> bool {
+        let cancel = [AtomicBool::new(false)];
+        let timeout = self.options.timeout;
+        let (solved, timed_out) = with_timeout(timeout, &cancel, || {
+            search_moves(
+                self,
+                &mut |_| true,
+                moves.as_deref(),
+                &cancel[0],
+                observer,
+                true,
+            )
+        });
+        self.stats.timed_out |= timed_out && !solved;
+        solved
+    }
+
+    // go on with the search for the first solution from `stack`, restored
+    // from a `SearchSnapshot`, on this thread
+    pub(crate) fn resume_first(
+        &mut self,
+        stack: Vec<Frame>,
+        restricted: Option<usize>,
+        observer: &mut impl SearchObserver,
+    ) -> Option<Solution> {
+        let cancel = [AtomicBool::new(false)];
+        let timeout = self.options.timeout;
+        let (solved, timed_out) = with_timeout(timeout, &cancel, || {
+            search_stack(
+                self,
+                stack,
+                restricted,
+                &mut |_| true,
+                &cancel[0],
+                observer,
+                true,
+            )
+        });
+        self.stats.timed_out |= timed_out && !solved;
+        self.solution_found(solved)
+    }
+
+    fn solution_found(&self, solved: bool) -> Option<Solution> {
+        if !solved {
+            return None;
+        }
+        info!("solution found");
+        debug_assert_eq!(self.validate_state(), Ok(()));
+        debug_assert!(self.is_solved());
+        debug!("{:?}", self.board);
+        debug!("{:?}", self.lines);
+        Some(Solution::new(self.result.clone(), self.frozen))
+    }
+
+    // search every distinct solution, stopping after `max` of them
+    //
+    // the same lines traced in another order are only reported once
+    pub fn solve_all(&mut self, max: Option<usize>) -> Vec<Solution> {
+        let mut solutions = Vec::new();
+        self.for_each_solution(max, |solution| {
+            solutions.push(solution);
+            ControlFlow::Continue(())
+        });
+        solutions
+    }
+
+    // call `each` with every distinct solution as soon as it is found, until
+    // it breaks or `max` solutions were found, without keeping them
+    //
+    // return the number of solutions found, none on a board failing
+    // `precheck` without searching
+    pub fn for_each_solution(
+        &mut self,
+        max: Option<usize>,
+        mut each: impl FnMut(Solution) -> ControlFlow<()>,
+    ) -> usize {
+        if self.precheck().is_err() {
+            return 0;
+        }
+        let mut seen = HashSet::new();
+        let cancel = [AtomicBool::new(false)];
+        let timeout = self.options.timeout;
+        let (_, timed_out) = with_timeout(timeout, &cancel, || {
+            let mut found = |board: &Board| {
+                let solution = Solution::new(board.result.clone(), board.frozen);
+                if !seen.insert(solution.normalized()) {
+                    return false;
+                }
+                info!("found solution {}", seen.len());
+                each(solution).is_break() || max.is_some_and(|max| seen.len() >= max)
+            };
+            search_moves(self, &mut found, None, &cancel[0], &mut (), false)
+        });
+        self.stats.timed_out |= timed_out;
+        seen.len()
+    }
+
+    // search at most two distinct solutions to tell whether there is exactly one
+    //
+    // a search that timed out reports the solutions found until then
+    pub fn uniqueness(&mut self) -> Uniqueness {
+        let mut solutions = self.solve_all(Some(2)).into_iter();
+        match (solutions.next(), solutions.next()) {
+            (None, _) => Uniqueness::NoSolution,
+            (Some(solution), None) => Uniqueness::Unique(solution),
+            (Some(first), Some(second)) => Uniqueness::Multiple(first, second),
+        }
+    }
+
+    // the color solved after `color`
+    fn next_color(&self, color: Color) -> Option<Color> {
+        let i = self.colors.iter().position(|&c| c == color)?;
+        self.colors.get(i + 1).copied()
+    }
+
+    // the end of the fixed lines of `color`, where its path goes on from
+    pub(crate) fn fixed_head(&self, color: Color) -> Option<Point> {
+        self.result[..self.frozen]
+            .iter()
+            .rev()
+            .find(|line| line.2 == color)
+            .map(|&(pos, direction, _)| direction.apply_offset(pos))
+    }
+}
+
+// A step of the depth-first search kept on an explicit stack, so long paths
+// cannot overflow the call stack
+pub(crate) enum Frame {
+    // the path of `color` started at the endpoint with index `start` at
+    // the time `since`, or continued from its fixed lines without `start`
+    Color {
+        color: Color,
+        start: Option<usize>,
+        since: Instant,
+    },
+    // the head of the path of `color` at `point`, with the moves left to try
+    // and the move taken from here that is on the board
+    Head {
+        point: Point,
+        color: Color,
+        directions: Vec<Direction>,
+        tried: usize,
+        line: Option<Direction>,
+    },
+}
+
+// run `search` while a watchdog sets every flag of `cancel` once `timeout`
+// is over, returning its result and whether the watchdog fired
+//
+// the search only polls a flag, so it never reads the clock
+//
+// there are no threads on wasm32, where the timeout is ignored
+pub(crate) fn with_timeout<R>(
+    timeout: Option<Duration>,
+    cancel: &[AtomicBool],
+    search: impl FnOnce() -> R,
+) -> (R, bool) {
+    let Some(timeout) = timeout.filter(|_| !cfg!(target_arch = "wasm32")) else {
+        return (search(), false);
+    };
+    let (done, finished) = mpsc::channel::<()>();
+    std::thread::scope(|scope| {
+        let watchdog = scope.spawn(move || {
+            let fired = finished.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+            if fired {
+                info!("timed out after {:?}", timeout);
+                for flag in cancel {
+                    flag.store(true, Ordering::Relaxed);
+                }
+            }
+            fired
+        });
+        let result = search();
+        drop(done);
+        (result, watchdog.join().unwrap())
+    })
+}
+
+// the moves out of the endpoint the search starts from, without those a
+// symmetry of the board makes redundant when pruning
+//
+// only the first solution is the same without them, enumerating every
+// solution has to try all moves
+pub(crate) fn first_moves(board: &Board) -> Option<Vec<Direction>> {
+    if !board.result.is_empty() {
+        // the search continues the lines already on the board
+        return None;
+    }
+    let color = *board.colors.first()?;
+    let start = (0..board.board.len())
+        .filter(|&i| board.board[i].0 == Symbol::color_end(color))
+        .min_by_key(|&i| node_order(board.pos(i)))
+        .map(|i| board.pos(i))?;
+    let moves = directions_from(board, start, color);
+    if board.options.prune {
+        Some(board.canonical_first_moves(start, moves))
+    } else {
+        Some(moves)
+    }
+}
+
+// search with the moves out of the first endpoint limited to `first_moves`,
+// giving up as soon as `cancel` is set
+//
+// with `first_only`, the search stops at the first solution, so a head whose
+// moves are all tried is a dead end: the lines on the board, the head and
+// its color are kept in a transposition table and the search never enters
+// the same state again
+pub(crate) fn search_moves(
+    board: &mut Board,
+    found: &mut Found,
+    first_moves: Option<&[Direction]>,
+    cancel: &AtomicBool,
+    observer: &mut impl SearchObserver,
+    first_only: bool,
+) -> bool {
+    let mut stack = Vec::new();
+    if start_color(board, &mut stack, board.colors.first().copied(), found) {
+        return true;
+    }
+    // the first head only tries some of its moves
+    let restricted = first_moves.map(|_| stack.len());
+    if let (Some(moves), Some(Frame::Head { directions, .. })) = (first_moves, stack.last_mut()) {
+        *directions = moves.to_vec();
+    }
+    search_stack(
+        board, stack, restricted, found, cancel, observer, first_only,
+    )
+}
+
+// go on with the search from the frames of `stack`, the head at the depth
+// `restricted` being the first one, whose moves may be limited
+//
+// the observer is offered a `SearchSnapshot` between moves and when the
+// search gives up, see `SearchObserver::wants_snapshot`
+pub(crate) fn search_stack(
+    board: &mut Board,
+    mut stack: Vec<Frame>,
+    restricted: Option<usize>,
+    found: &mut Found,
+    cancel: &AtomicBool,
+    observer: &mut impl SearchObserver,
+    first_only: bool,
+) -> bool {
+    let capacity = if first_only {
+        board.options.transposition_size
+    } else {
+        0
+    };
+    let mut dead: HashSet<(u64, Point, Color)> = HashSet::new();
+    'search: loop {
+        if observer.wants_snapshot(false) {
+            observer.on_snapshot(SearchSnapshot::new(board, &stack, restricted));
+        }
+        let Some(frame) = stack.last_mut() else {
+            break;
+        };
+        let (point, color, directions, tried, line) = match frame {
+            Frame::Color {
+                color,
+                start,
+                since,
+            } => {
+                // backtrack to previous color
+                info!("backtrack from color {}", color);
+                if let Some(start) = start {
+                    board.board[*start].1 -= 1;
+                }
+                board.stats.color_time[color.index()] += since.elapsed();
+                stack.pop();
+                continue;
+            }
+            Frame::Head {
+                point,
+                color,
+                directions,
+                tried,
+                line,
+            } => (*point, *color, directions, tried, line),
+        };
+        if let Some(direction) = line.take() {
+            let removed = board.remove_line(point, direction);
+            debug_assert_eq!(removed, Ok(()));
+            observer.on_remove(board, point, direction, color);
+        }
+        while let Some(&direction) = directions.get(*tried) {
+            if cancel.load(Ordering::Relaxed) || cancelled(board) || out_of_budget(board) {
+                stop_color_timers(board, &stack);
+                if observer.wants_snapshot(true) {
+                    observer.on_snapshot(SearchSnapshot::new(board, &stack, restricted));
+                }
+                return false;
+            }
+            *tried += 1;
+            let next_point = direction.apply_offset(point);
+            if board.options.prune && board.in_bounds(next_point) {
+                if let (Symbol::White(n), count) = board.board[board.index(next_point)] {
+                    if count == n {
+                        // no pass left through this white node
+                        continue;
+                    }
+                }
+            }
+            if board.add_line(point, direction, color).is_err() {
+                continue;
+            }
+            observer.on_place(board, point, direction, color);
+            if board.options.prune && !board.whites_feasible_around(point, next_point) {
+                trace!("white node starved around {:?}", next_point);
+            } else if board.board[board.index(next_point)].0 == Symbol::color_end(color) {
+                board.stats.color_checks += 1;
+                if color_solved(board, color) {
+                    info!("solved color {:?}", color);
+                    observer.on_color_completed(board, color);
+                    *line = Some(direction);
+                    let next_color = board.next_color(color);
+                    if start_color(board, &mut stack, next_color, found) {
+                        stop_color_timers(board, &stack);
+                        return true;
+                    } // else continue to solve this color
+                    continue 'search;
+                } else {
+                    trace!("color {:?} reach to end but not all connected", color);
+                }
+            } else if capacity > 0 && dead.contains(&(board.hash, next_point, color)) {
+                board.stats.transposition_hits += 1;
+                trace!("dead end at {:?} seen before", next_point);
+            } else if board.options.prune && !board.remaining_reachable(next_point, color) {
+                trace!("color {:?} cut off at {:?}", color, next_point);
+            } else {
+                trace!("solving {:?} at {:?}", color, next_point);
+                *line = Some(direction);
+                stack.push(Frame::Head {
+                    point: next_point,
+                    color,
+                    directions: directions_from(board, next_point, color),
+                    tried: 0,
+                    line: None,
+                });
+                continue 'search;
+            }
+            let removed = board.remove_line(point, direction);
+            debug_assert_eq!(removed, Ok(()));
+            observer.on_remove(board, point, direction, color);
+        }
+        if capacity > 0 && restricted != Some(stack.len()) {
+            if dead.len() >= capacity {
+                dead.clear();
+            }
+            dead.insert((board.hash, point, color));
+        }
+        stack.pop();
+    }
+    false
+}
+
+// start the path of `color` at its first endpoint, skipping to the next
+// colors while they have none
+//
+// return whether the search is over, which only happens once every color
+// is connected
+fn start_color(
+    board: &mut Board,
+    stack: &mut Vec<Frame>,
+    mut color: Option<Color>,
+    found: &mut Found,
+) -> bool {
+    while let Some(c) = color {
+        // the path continues from the end of its fixed lines
+        if let Some(head) = board.fixed_head(c) {
+            if board.symbol(head) != Some(Symbol::color_end(c)) {
+                info!("continuing color {} from {:?}", c, head);
+                stack.push(Frame::Color {
+                    color: c,
+                    start: None,
+                    since: Instant::now(),
+                });
+                stack.push(Frame::Head {
+                    point: head,
+                    color: c,
+                    directions: directions_from(board, head, c),
+                    tried: 0,
+                    line: None,
+                });
+                return false;
+            }
+            if !color_solved(board, c) {
+                info!("fixed lines of color {} miss some of its nodes", c);
+                return false;
+            }
+            color = board.next_color(c);
+            continue;
+        }
+        let start = (0..board.board.len())
+            .filter(|&i| board.board[i].0 == Symbol::color_end(c))
+            .min_by_key(|&i| node_order(board.pos(i)));
+        if let Some(start) = start {
+            info!("solving color {}", c);
+            debug!("{:?}", board.board);
+            board.board[start].1 += 1;
+            let point = board.pos(start);
+            stack.push(Frame::Color {
+                color: c,
+                start: Some(start),
+                since: Instant::now(),
+            });
+            stack.push(Frame::Head {
+                point,
+                color: c,
+                directions: directions_from(board, point, c),
+                tried: 0,
+                line: None,
+            });
+            return false;
+        }
+        info!("no start found for color {}", c);
+        color = board.next_color(c);
+    }
+    info!("all color connected");
+    board.stats.white_checks += 1;
+    white_solved(board) && found(board)
+}
+
+// whether the token of the options was cancelled, giving up the search
+pub(crate) fn cancelled(board: &mut Board) -> bool {
+    let cancelled = board
+        .options
+        .cancellation
+        .as_ref()
+        .is_some_and(CancellationToken::is_cancelled);
+    board.stats.cancelled |= cancelled;
+    cancelled
+}
+
+// whether the search used up `max_attempts`, giving up as on a timeout
+pub(crate) fn out_of_budget(board: &mut Board) -> bool {
+    let spent = board
+        .options
+        .max_attempts
+        .is_some_and(|max| board.stats.add_line_calls >= max);
+    if spent {
+        board.stats.out_of_budget = true;
+        board.stats.timed_out = true;
+    }
+    spent
+}
+
+// account the time of the colors still searched when the search stops
+fn stop_color_timers(board: &mut Board, stack: &[Frame]) {
+    for frame in stack {
+        if let Frame::Color { color, since, .. } = frame {
+            board.stats.color_time[color.index()] += since.elapsed();
+        }
+    }
+}
+
+// the moves from `point` in the order they are tried
+pub(crate) fn directions_from(board: &Board, point: Point, color: Color) -> Vec<Direction> {
+    let mut directions = Direction::iter().collect::<Vec<_>>();
+    match board.options.direction_order {
+        DirectionOrder::Fixed => {}
+        DirectionOrder::Greedy => {
+            let nodes_left = board
+                .board
+                .iter()
+                .any(|&(symbol, counter)| symbol == Symbol::color(color) && counter == 0);
+            // stable, so equal scores keep the tie-break order
+            directions.sort_by_key(|direction| {
+                let next_point = direction.apply_offset(point);
+                if !board.in_bounds(next_point) {
+                    return 3;
+                }
+                match board.board[board.index(next_point)] {
+                    (symbol, 0) if symbol == Symbol::color(color) => 0,
+                    (Symbol::White(n), counter) if counter < n => 1,
+                    (symbol, _) if symbol == Symbol::color_end(color) && nodes_left => 3,
+                    _ => 2,
+                }
+            });
+        }
+        DirectionOrder::Target => {
+            let targets = (0..board.board.len())
+                .filter(|&i| board.board[i] == (Symbol::color_end(color), 0))
+                .map(|i| board.pos(i))
+                .collect::<Vec<_>>();
+            // moves are one step in any of the 8 directions
+            let distance = |(x, y): Point| {
+                targets
+                    .iter()
+                    .map(|&(tx, ty)| (tx - x).abs().max((ty - y).abs()))
+                    .min()
+                    .unwrap_or(0)
+            };
+            directions.sort_by_key(|direction| {
+                let next_point = direction.apply_offset(point);
+                if board.in_bounds(next_point) {
+                    distance(next_point)
+                } else {
+                    i32::MAX
+                }
+            });
+        }
+        DirectionOrder::Random(seed) => {
+            let (x, y) = point;
+            let key = ((x as u64) << 40) ^ ((y as u64) << 16) ^ color.index() as u64;
+            Rng(seed ^ Rng(key).next()).shuffle(&mut directions);
+        }
+    }
+    directions
+}
+
+// whether every node of `color` has a line
+pub(crate) fn color_solved(board: &Board, color: Color) -> bool {
+    let solved = board.uncovered[color.index()] == 0;
+    #[cfg(debug_assertions)]
+    assert_eq!(solved, color_solved_slow(board, color));
+    solved
+}
+
+// `color_solved` from the lines alone, to check the counters
+#[cfg(debug_assertions)]
+fn color_solved_slow(board: &Board, color: Color) -> bool {
+    let mut board_clone = board.board.iter().map(|s| s.0).collect::<Vec<_>>();
+    for (i, line) in board.lines.iter().enumerate() {
+        for (dir, line_color) in line.iter().enumerate() {
+            if let Some(line_color) = line_color {
+                if *line_color == color {
+                    let direction: Direction = crate::DirectionInner::from(dir as u8).into();
+                    let pos = board.pos(i);
+
+                    let i2 = board.index(direction.apply_offset(pos));
+                    board_clone[i] = Symbol::Empty;
+                    board_clone[i2] = Symbol::Empty;
+                }
+            }
+        }
+    }
+
+    !board_clone.contains(&Symbol::color(color))
+}
+
+// whether every white node is passed its number of times
+pub(crate) fn white_solved(board: &Board) -> bool {
+    let solved = board.whites_left == 0;
+    #[cfg(debug_assertions)]
+    assert_eq!(solved, white_solved_slow(board));
+    solved
+}
+
+// `white_solved` from the counts alone, to check `whites_left`
+#[cfg(debug_assertions)]
+fn white_solved_slow(board: &Board) -> bool {
+    for (c, count) in board.board.iter() {
+        if let Symbol::White(n) = c {
+            if n != count {
+                info!("white not solved, backtrack");
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a single red path covering a 4x3 board, which many paths do
+    const TIES: &str = "RrrR\nrrrr\nrrrr";
+
+    // a 3x4 board with three colors and a single solution
+    const UNIQUE: &str = "R2B\n2Gr\ngbR\n.GB";
+
+    // a 5x5 board with three colors and eight white nodes
+    const WHITES: &str = "bb2R.\nb222R\nB32Br\nG222.\nGgggg";
+
+    // a single red path winding through `rows` rows of `width` nodes, each
+    // row joined to the next by a white node at alternate ends
+    fn snake(width: usize, rows: usize) -> String {
+        let mut lines = Vec::new();
+        for row in 0..rows {
+            let mut line = vec!['r'; width];
+            if row == 0 {
+                line[0] = 'R';
+            }
+            if row == rows - 1 {
+                line[if row % 2 == 0 { width - 1 } else { 0 }] = 'R';
+            }
+            lines.push(line);
+            if row < rows - 1 {
+                let mut joint = vec!['.'; width];
+                joint[if row % 2 == 0 { width - 1 } else { 0 }] = '1';
+                lines.push(joint);
+            }
+        }
+        lines
+            .iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn solved(text: &str, options: SolveOptions) -> (Solution, Stats) {
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            threads: 1,
+            ..options
+        });
+        let solution = board.solve().unwrap();
+        (solution, board.stats().clone())
+    }
+
+    fn first_solution(direction_order: DirectionOrder) -> Vec<(Point, Direction, Color)> {
+        let mut board = Board::parse(TIES).unwrap();
+        board.set_options(SolveOptions {
+            direction_order,
+            threads: 1,
+            ..SolveOptions::default()
+        });
+        board.solve().unwrap().segments().to_vec()
+    }
+
+    #[test]
+    fn ties_give_the_same_solution_on_every_run() {
+        for order in [
+            DirectionOrder::Fixed,
+            DirectionOrder::Greedy,
+            DirectionOrder::Target,
+            DirectionOrder::Random(7),
+        ] {
+            let first = first_solution(order);
+            for _ in 0..5 {
+                assert_eq!(first_solution(order), first, "{:?}", order);
+            }
+        }
+    }
+
+    #[test]
+    fn ties_list_every_solution_in_the_same_order() {
+        let all = || {
+            Board::parse(TIES)
+                .unwrap()
+                .solve_all(None)
+                .iter()
+                .map(|solution| solution.segments().to_vec())
+                .collect::<Vec<_>>()
+        };
+        let first = all();
+        assert!(first.len() > 1);
+        assert_eq!(all(), first);
+    }
+
+    #[test]
+    fn every_color_order_finds_the_same_lines() {
+        let (expected, _) = solved(UNIQUE, SolveOptions::default());
+        let (red, green, blue) = (Color::Red, Color::Green, Color::Blue);
+        for order in [
+            [red, green, blue],
+            [red, blue, green],
+            [green, red, blue],
+            [green, blue, red],
+            [blue, red, green],
+            [blue, green, red],
+        ] {
+            let options = SolveOptions {
+                color_order: Some(order.to_vec()),
+                ..SolveOptions::default()
+            };
+            let (solution, _) = solved(UNIQUE, options);
+            assert_eq!(solution.normalized(), expected.normalized(), "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn fewest_nodes_first_beats_a_bad_color_order() {
+        let (_, ranked) = solved(WHITES, SolveOptions::default());
+        let options = SolveOptions {
+            color_order: Some(vec![Color::Blue, Color::Green, Color::Red]),
+            ..SolveOptions::default()
+        };
+        let (_, fixed) = solved(WHITES, options);
+        assert!(
+            ranked.add_line_calls * 10 < fixed.add_line_calls,
+            "{} attempts ranked, {} blue first",
+            ranked.add_line_calls,
+            fixed.add_line_calls
+        );
+    }
+
+    #[test]
+    fn greedy_directions_explore_fewer_nodes() {
+        for text in [UNIQUE, WHITES] {
+            let (_, fixed) = solved(text, SolveOptions::default());
+            let options = SolveOptions {
+                direction_order: DirectionOrder::Greedy,
+                ..SolveOptions::default()
+            };
+            let (_, greedy) = solved(text, options);
+            assert!(
+                greedy.add_line_calls < fixed.add_line_calls,
+                "{} attempts greedy, {} fixed on\n{}",
+                greedy.add_line_calls,
+                fixed.add_line_calls,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn a_long_snake_fits_in_a_small_stack() {
+        let text = snake(40, 20);
+        // the path is longer than the frames of a recursive search that fit
+        // in the stack of this thread
+        let solution = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || solved(&text, SolveOptions::default()).0)
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(solution.segments().len(), 40 * 20 + 19 - 1);
+    }
+
+    #[test]
+    fn the_transposition_table_never_changes_the_verdict() {
+        let mut rng = crate::rng::Rng(784);
+        let mut solvable = 0;
+        for _ in 0..300 {
+            let (width, height) = (3 + rng.below(2), 3 + rng.below(2));
+            let text = rng.board_text(width, height);
+            for prune in [true, false] {
+                let verdict = |transposition_size| {
+                    let mut board = Board::parse(&text).unwrap();
+                    board.set_options(SolveOptions {
+                        prune,
+                        transposition_size,
+                        threads: 1,
+                        ..SolveOptions::default()
+                    });
+                    board.solve().is_some()
+                };
+                let solved = verdict(0);
+                assert_eq!(verdict(1 << 20), solved, "{}", text);
+                assert_eq!(verdict(4), solved, "{}", text);
+                solvable += solved as usize;
+            }
+        }
+        assert!(solvable > 0 && solvable < 600, "{} solvable", solvable);
+    }
+
+    #[test]
+    fn the_transposition_table_skips_dead_ends_seen_before() {
+        let (_, remembered) = solved(WHITES, SolveOptions::default());
+        let options = SolveOptions {
+            transposition_size: 0,
+            ..SolveOptions::default()
+        };
+        let (_, forgotten) = solved(WHITES, options);
+        assert!(remembered.transposition_hits > 0);
+        assert!(
+            remembered.add_line_calls < forgotten.add_line_calls,
+            "{} attempts with the table, {} without",
+            remembered.add_line_calls,
+            forgotten.add_line_calls
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use crate::{Color, Direction, Point};
+
+// Counters of the work done by the searches on a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    // calls to `add_line`, legal or not
+    pub add_line_calls: u64,
+    // lines actually placed
+    pub lines_placed: u64,
+    // lines taken back by `remove_line`
+    pub backtracks: u64,
+    // the most lines on the board at once
+    pub max_depth: usize,
+    // the lines on the board when it held the most of them
+    pub deepest: Vec<(Point, Direction, Color)>,
+    // times a path reached its last endpoint and its nodes were checked
+    pub color_checks: u64,
+    // times the white nodes were checked once every color was connected
+    pub white_checks: u64,
+    // states skipped as dead ends already searched
+    pub transposition_hits: u64,
+    // time spent searching each color, including the colors solved after
+    // it, by `Color::index`
+    pub color_time: Vec<Duration>,
+    // whether a search gave up on its timeout or its attempt budget
+    pub timed_out: bool,
+    // whether a search gave up after `SolveOptions::max_attempts`
+    pub out_of_budget: bool,
+    // whether a search gave up on `SolveOptions::cancellation`
+    pub cancelled: bool,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            add_line_calls: 0,
+            lines_placed: 0,
+            backtracks: 0,
+            max_depth: 0,
+            deepest: Vec::new(),
+            color_checks: 0,
+            white_checks: 0,
+            transposition_hits: 0,
+            color_time: vec![Duration::ZERO; Color::iter().count()],
+            timed_out: false,
+            out_of_budget: false,
+            cancelled: false,
+        }
+    }
+}
+
+impl Stats {
+    // add the counters of a search run on a copy of the board
+    pub(crate) fn merge(&mut self, other: &Stats) {
+        self.add_line_calls += other.add_line_calls;
+        self.lines_placed += other.lines_placed;
+        self.backtracks += other.backtracks;
+        if other.max_depth > self.max_depth {
+            self.max_depth = other.max_depth;
+            self.deepest = other.deepest.clone();
+        }
+        self.color_checks += other.color_checks;
+        self.white_checks += other.white_checks;
+        self.transposition_hits += other.transposition_hits;
+        for (time, other) in self.color_time.iter_mut().zip(&other.color_time) {
+            *time += *other;
+        }
+        self.timed_out |= other.timed_out;
+        self.out_of_budget |= other.out_of_budget;
+        self.cancelled |= other.cancelled;
+    }
+}
+
+impl std::fmt::Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "add_line calls: {}", self.add_line_calls)?;
+        writeln!(f, "lines placed: {}", self.lines_placed)?;
+        writeln!(f, "backtracks: {}", self.backtracks)?;
+        writeln!(f, "max depth: {}", self.max_depth)?;
+        writeln!(f, "color checks: {}", self.color_checks)?;
+        writeln!(f, "white checks: {}", self.white_checks)?;
+        write!(f, "transposition hits: {}", self.transposition_hits)?;
+        if self.cancelled {
+            write!(f, "\ncancelled")?;
+        } else if self.out_of_budget {
+            write!(f, "\nout of attempts")?;
+        } else if self.timed_out {
+            write!(f, "\ntimed out")?;
+        }
+        for color in Color::iter() {
+            let time = self.color_time[color.index()];
+            if !time.is_zero() {
+                write!(f, "\n{} time: {}", color, format_duration(time))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// `duration` with a unit fitting its size, `850 µs`, `12.346 ms`, `3.210 s`
+// or `2 min 5.3 s`
+//
+// the duration is rounded to the precision of a unit before checking that
+// it fits, so 999.9996 ms is `1.000 s` rather than `1000.000 ms`
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    // the duration in `unit` nanoseconds, rounded half up
+    let rounded = |unit: u128| (nanos + unit / 2) / unit;
+    let micros = rounded(1_000);
+    let millis = rounded(1_000_000);
+    if micros < 1_000 {
+        format!("{} µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{}.{:03} ms", micros / 1_000, micros % 1_000)
+    } else if millis < 60_000 {
+        format!("{}.{:03} s", millis / 1_000, millis % 1_000)
+    } else {
+        let tenths = rounded(100_000_000);
+        let (minutes, tenths) = (tenths / 600, tenths % 600);
+        format!("{} min {}.{} s", minutes, tenths / 10, tenths % 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_take_the_unit_of_their_rounded_value() {
+        let cases = [
+            (Duration::from_nanos(850_400), "850 µs"),
+            (Duration::from_nanos(999_600), "1.000 ms"),
+            (Duration::from_nanos(12_345_600), "12.346 ms"),
+            (Duration::from_nanos(999_999_600), "1.000 s"),
+            (Duration::from_millis(3_210), "3.210 s"),
+            (Duration::from_nanos(59_999_600_000), "1 min 0.0 s"),
+            (Duration::from_millis(125_300), "2 min 5.3 s"),
+            (Duration::from_millis(119_960), "2 min 0.0 s"),
+        ];
+        for (duration, text) in cases {
+            assert_eq!(format_duration(duration), text, "{:?}", duration);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt::Write;
+
+use crate::{Board, Color, Direction, Point, Solution, Symbol};
+
+impl Color {
+    // fill color of the nodes and lines
+    fn svg(self) -> String {
+        match self {
+            Color::Red => "#d32f2f".into(),
+            Color::Green => "#388e3c".into(),
+            Color::Blue => "#1976d2".into(),
+            Color::Yellow => "#fbc02d".into(),
+            Color::Cyan => "#0097a7".into(),
+            Color::Orange => "#f57c00".into(),
+            // the other colors spread around the color wheel
+            _ => format!("hsl({}, 65%, 45%)", self.index() * 137 % 360),
+        }
+    }
+
+    // corners of the shape of the nodes of the color, of radius `r` around
+    // `(cx, cy)`, as in the game: triangles, squares and diamonds, and
+    // hexagons for the colors the game does not have
+    fn svg_shape(self, (cx, cy): (f64, f64), r: f64) -> String {
+        let corners: &[(f64, f64)] = match self {
+            Color::Red => &[(0.0, -1.0), (0.866, 0.5), (-0.866, 0.5)],
+            Color::Green => &[(-0.75, -0.75), (0.75, -0.75), (0.75, 0.75), (-0.75, 0.75)],
+            Color::Blue => &[(0.0, -1.0), (1.0, 0.0), (0.0, 1.0), (-1.0, 0.0)],
+            _ => &[
+                (0.0, -1.0),
+                (0.866, -0.5),
+                (0.866, 0.5),
+                (0.0, 1.0),
+                (-0.866, 0.5),
+                (-0.866, -0.5),
+            ],
+        };
+        corners
+            .iter()
+            .map(|(x, y)| format!("{},{}", cx + x * r, cy + y * r))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Solution {
+    // draw the solution as an SVG image, with `cell_size` pixels between nodes
+    pub fn to_svg(&self, board: &Board, cell_size: u32) -> String {
+        svg(board, self.segments(), cell_size)
+    }
+}
+
+impl Board {
+    // draw the nodes of the board as an SVG image, without any line
+    pub fn to_svg(&self, cell_size: u32) -> String {
+        svg(self, &[], cell_size)
+    }
+}
+
+fn svg(board: &Board, segments: &[(Point, Direction, Color)], cell_size: u32) -> String {
+    let size = cell_size as f64;
+    // node centers sit in the middle of their cell, so beveled lines pass
+    // right between the 4 nodes around them
+    let center = |(x, y): Point| ((x as f64 + 0.5) * size, (y as f64 + 0.5) * size);
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = board.width() as u32 * cell_size,
+        h = board.height() as u32 * cell_size,
+    )
+    .unwrap();
+
+    for &(pos, direction, color) in segments {
+        let (x1, y1) = center(pos);
+        let (x2, y2) = center(direction.apply_offset(pos));
+        writeln!(
+            svg,
+            r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+            color.svg(),
+            size * 0.1,
+        )
+        .unwrap();
+    }
+
+    for y in 0..board.height() as i32 {
+        for x in 0..board.width() as i32 {
+            let symbol = board.board[board.index((x, y))].0;
+            let (cx, cy) = center((x, y));
+            match (symbol, symbol.node_color()) {
+                (Symbol::White(n), _) => writeln!(
+                    svg,
+                    r#"<circle cx="{cx}" cy="{cy}" r="{r}" fill="white" stroke="black" stroke-width="{s}"/><text x="{cx}" y="{cy}" font-size="{r}" text-anchor="middle" dominant-baseline="central">{n}</text>"#,
+                    r = size * 0.25,
+                    s = size * 0.04,
+                ),
+                // endpoints are larger and hollow
+                (symbol, Some(color)) if symbol.is_end() => writeln!(
+                    svg,
+                    r#"<polygon points="{}" fill="{}"/><polygon points="{}" fill="white"/>"#,
+                    color.svg_shape((cx, cy), size * 0.35),
+                    color.svg(),
+                    color.svg_shape((cx, cy), size * 0.15),
+                ),
+                (_, Some(color)) => writeln!(
+                    svg,
+                    r#"<polygon points="{}" fill="{}"/>"#,
+                    color.svg_shape((cx, cy), size * 0.25),
+                    color.svg(),
+                ),
+                (_, None) => Ok(()),
+            }
+            .unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,133 @@
+// Letters of the colors in the order they are declared: the colors of the
+// game first, then the rest of the alphabet
+const LETTERS: &[u8; 26] = b"rgbycoadefhijklmnpqstuvwxz";
+const NAMES: [&str; 6] = ["Red", "Green", "Blue", "Yellow", "Cyan", "Orange"];
+
+// A color of the puzzle, any letter of the alphabet in the text format
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Color(u8);
+
+#[allow(non_upper_case_globals)]
+impl Color {
+    pub const Red: Color = Color(0);
+    pub const Green: Color = Color(1);
+    pub const Blue: Color = Color(2);
+    pub const Yellow: Color = Color(3);
+    pub const Cyan: Color = Color(4);
+    pub const Orange: Color = Color(5);
+
+    // every color, in declaration order
+    pub fn iter() -> impl Iterator<Item = Color> {
+        (0..LETTERS.len() as u8).map(Color)
+    }
+
+    pub fn next(self) -> Option<Self> {
+        Some(Color(self.0 + 1)).filter(|c| c.index() < LETTERS.len())
+    }
+
+    // the color written with `letter` in either case
+    pub fn from_letter(letter: char) -> Option<Self> {
+        let letter = letter.to_ascii_lowercase();
+        LETTERS
+            .iter()
+            .position(|&l| char::from(l) == letter)
+            .map(|i| Color(i as u8))
+    }
+
+    // the lowercase letter of the color's nodes
+    pub fn letter(self) -> char {
+        char::from(LETTERS[self.index()])
+    }
+
+    // position of the color in declaration order, to index tables by color
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+// colors of the game by their name, the others by their endpoint letter
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match NAMES.get(self.index()) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", self.letter().to_ascii_uppercase()),
+        }
+    }
+}
+
+impl std::fmt::Debug for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+// Colors allowed to pass through a white node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSet(u32);
+
+impl ColorSet {
+    pub const ALL: ColorSet = ColorSet(u32::MAX);
+
+    pub fn empty() -> Self {
+        ColorSet(0)
+    }
+
+    pub fn insert(&mut self, c: Color) {
+        self.0 |= 1 << c.0;
+    }
+
+    pub fn contains(self, c: Color) -> bool {
+        self.0 & (1 << c.0) != 0
+    }
+}
+
+// the most times a white node can be passed through, each pass taking two
+// of the lines to its 8 neighbors
+pub(crate) const MAX_WHITE: u8 = 4;
+
+// Puzzle nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    // a node on the path of a color
+    Node(Color),
+    // a start or end point of a color
+    End(Color),
+    Empty,
+    White(u8),
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let c = match self {
+            Symbol::Node(color) => color.letter(),
+            Symbol::End(color) => color.letter().to_ascii_uppercase(),
+            Symbol::Empty => '.',
+            Symbol::White(n @ 0..=9) => return write!(f, "{}", n),
+            // a single character is read as a white node of its own
+            Symbol::White(n) => return write!(f, "({})", n),
+        };
+        write!(f, "{}", c)
+    }
+}
+
+impl Symbol {
+    pub fn color(c: Color) -> Self {
+        Symbol::Node(c)
+    }
+
+    pub fn color_end(c: Color) -> Self {
+        Symbol::End(c)
+    }
+
+    // the color of a colored node or endpoint
+    pub fn node_color(self) -> Option<Color> {
+        match self {
+            Symbol::Node(c) | Symbol::End(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn is_end(self) -> bool {
+        matches!(self, Symbol::End(_))
+    }
+}
@@ -0,0 +1,126 @@
+use log::debug;
+
+use crate::{Board, Direction, Point};
+
+// A transform of the board onto itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    // mirror left and right
+    FlipHorizontal,
+    // mirror top and bottom
+    FlipVertical,
+    Rotate180,
+}
+
+impl Symmetry {
+    fn apply(self, board: &Board, (x, y): Point) -> Point {
+        let (w, h) = (board.width as i32 - 1, board.height as i32 - 1);
+        match self {
+            Symmetry::FlipHorizontal => (w - x, y),
+            Symmetry::FlipVertical => (x, h - y),
+            Symmetry::Rotate180 => (w - x, h - y),
+        }
+    }
+
+    fn apply_direction(self, direction: Direction) -> Direction {
+        let (dx, dy) = direction.offset();
+        let offset = match self {
+            Symmetry::FlipHorizontal => (-dx, dy),
+            Symmetry::FlipVertical => (dx, -dy),
+            Symmetry::Rotate180 => (-dx, -dy),
+        };
+        Direction::from_offset(offset).unwrap()
+    }
+}
+
+impl Board {
+    // the symmetries of the board, lines excluded, that leave `pos` in place
+    fn symmetries_fixing(&self, pos: Point) -> Vec<Symmetry> {
+        [
+            Symmetry::FlipHorizontal,
+            Symmetry::FlipVertical,
+            Symmetry::Rotate180,
+        ]
+        .into_iter()
+        .filter(|&symmetry| symmetry.apply(self, pos) == pos)
+        .filter(|&symmetry| {
+            (0..self.board.len()).all(|i| {
+                let j = self.index(symmetry.apply(self, self.pos(i)));
+                self.board[i].0 == self.board[j].0 && self.allowed[i] == self.allowed[j]
+            })
+        })
+        .collect()
+    }
+
+    // drop the first moves out of the endpoint `start` that a symmetry maps
+    // onto a move tried before them
+    //
+    // the symmetry maps the solutions after one move onto the solutions
+    // after the other, so the first solution found stays the same
+    pub(crate) fn canonical_first_moves(
+        &self,
+        start: Point,
+        moves: Vec<Direction>,
+    ) -> Vec<Direction> {
+        if !self.result.is_empty() {
+            return moves;
+        }
+        let symmetries = self.symmetries_fixing(start);
+        if !symmetries.is_empty() {
+            debug!("symmetries fixing {:?}: {:?}", start, symmetries);
+        }
+        moves
+            .iter()
+            .enumerate()
+            .filter(|&(i, &direction)| {
+                !symmetries
+                    .iter()
+                    .any(|symmetry| moves[..i].contains(&symmetry.apply_direction(direction)))
+            })
+            .map(|(_, &direction)| direction)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solver::first_moves;
+    use crate::{Board, SolveOptions};
+
+    // the board of `text` after the search for its first solution, with the
+    // first moves limited by the symmetries of the board or not
+    fn searched(text: &str, symmetry: bool) -> Board {
+        let mut board = Board::parse(text).unwrap();
+        board.set_options(SolveOptions {
+            threads: 1,
+            ..SolveOptions::default()
+        });
+        let moves = if symmetry { first_moves(&board) } else { None };
+        board.search_first(moves, &mut ());
+        board
+    }
+
+    #[test]
+    fn a_mirrored_board_explores_fewer_nodes() {
+        // left and right mirror each other, and there is no solution so the
+        // whole tree is explored
+        let text = "rRr\nr.r\nrRr\nrrr";
+        let (with, without) = (searched(text, true), searched(text, false));
+        assert!(!with.is_solved() && !without.is_solved());
+        let (with, without) = (with.stats(), without.stats());
+        assert!(
+            with.add_line_calls < without.add_line_calls,
+            "{} attempts with symmetry, {} without",
+            with.add_line_calls,
+            without.add_line_calls
+        );
+    }
+
+    #[test]
+    fn a_mirrored_board_keeps_its_first_solution() {
+        let text = "rrRrr\nrrrrr\nrrRrr";
+        let (with, without) = (searched(text, true), searched(text, false));
+        assert!(with.is_solved());
+        assert_eq!(with.export_lines(), without.export_lines());
+    }
+}
@@ -0,0 +1,562 @@
+use strum::IntoEnumIterator;
+
+use crate::{Board, Color, Direction, ParseError, Point, Solution, Symbol};
+
+// A reason why a board can never be solved, found before searching
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    // a color with another number of endpoints than 0 or 2
+    EndpointCount {
+        color: Color,
+        count: usize,
+    },
+    // a node at `pos` whose color has no endpoint to draw its path from
+    NoEndpoints {
+        color: Color,
+        pos: Point,
+    },
+    // a white node with too few neighbors to be passed `required` times
+    WhiteCapacity {
+        pos: Point,
+        required: u8,
+        neighbors: usize,
+    },
+    // a colored node without any neighbor its color can connect to
+    Unreachable {
+        color: Color,
+        pos: Point,
+    },
+    // a colored node that is not an endpoint, so a path enters and leaves
+    // it, with a single neighbor its color can connect to
+    DeadEnd {
+        color: Color,
+        pos: Point,
+    },
+    // a colored node at `pos` missing more lines than the `free` ones it
+    // could still draw, to nodes of its color or white nodes with room left
+    MissingLines {
+        color: Color,
+        pos: Point,
+        missing: u8,
+        free: u8,
+    },
+    // a white node whose `free` lines cannot make up the lines it misses
+    // with an even number of each color, as every pass enters and leaves
+    WhiteParity {
+        pos: Point,
+        missing: u8,
+        free: u8,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::EndpointCount { color, count } => write!(
+                f,
+                "There are {} {} endpoints, but there should be 0 or 2",
+                count, color
+            ),
+            ValidationError::NoEndpoints { color, pos } => write!(
+                f,
+                "{} node {:?} cannot be connected, there is no {} endpoint",
+                color, pos, color
+            ),
+            ValidationError::WhiteCapacity {
+                pos,
+                required,
+                neighbors,
+            } => write!(
+                f,
+                "white node {:?} needs {} lines but has only {} neighbors",
+                pos,
+                usize::from(*required) * 2,
+                neighbors
+            ),
+            ValidationError::Unreachable { color, pos } => write!(
+                f,
+                "{} node {:?} has no neighbor it can connect to",
+                color, pos
+            ),
+            ValidationError::DeadEnd { color, pos } => write!(
+                f,
+                "{} node {:?} is passed through but has a single neighbor it can connect to",
+                color, pos
+            ),
+            ValidationError::MissingLines {
+                color,
+                pos,
+                missing,
+                free,
+            } => write!(
+                f,
+                "{} node {:?} misses {} lines but can only draw {} more",
+                color, pos, missing, free
+            ),
+            ValidationError::WhiteParity { pos, missing, free } => write!(
+                f,
+                "white node {:?} misses {} lines, an even number of each color, which its {} free \
+                 lines cannot make up",
+                pos, missing, free
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+// Every problem `Board::validate` found on a board, in the order of the
+// checks: the endpoints of each color, then the nodes in reading order
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    problems: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    // whether no problem was found, which does not mean a solution exists
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn problems(&self) -> &[ValidationError] {
+        &self.problems
+    }
+
+    // Ok if no problem was found, or the report as an error
+    pub fn into_result(self) -> Result<(), ValidationReport> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl IntoIterator for ValidationReport {
+    type Item = ValidationError;
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.problems.into_iter()
+    }
+}
+
+// one problem per line
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, problem) in self.problems.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+// A puzzle that cannot be read, or can obviously never be solved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    Parse(ParseError),
+    Invalid(ValidationReport),
+}
+
+impl std::fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleError::Parse(e) => write!(f, "{}", e),
+            PuzzleError::Invalid(report) => write!(f, "{}", report),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+// read the puzzle `text`, check it and search its first solution with the
+// default options, the whole job of the command line in one call
+pub fn solve(text: &str) -> Result<Option<Solution>, PuzzleError> {
+    let mut board = Board::parse(text).map_err(PuzzleError::Parse)?;
+    board
+        .validate()
+        .into_result()
+        .map_err(PuzzleError::Invalid)?;
+    Ok(board.solve())
+}
+
+impl Board {
+    // check the board for problems that make it obviously unsolvable,
+    // reporting all of them at once
+    pub fn validate(&self) -> ValidationReport {
+        let mut errors = Vec::new();
+        for color in Color::iter() {
+            let count = self
+                .symbols()
+                .filter(|&s| s == Symbol::color_end(color))
+                .count();
+            if count != 0 && count != 2 {
+                errors.push(ValidationError::EndpointCount { color, count });
+            }
+            if count == 0 {
+                if let Some(index) = self.symbols().position(|s| s == Symbol::color(color)) {
+                    errors.push(ValidationError::NoEndpoints {
+                        color,
+                        pos: self.pos(index),
+                    });
+                }
+            }
+        }
+
+        for index in 0..self.board.len() {
+            let pos = self.pos(index);
+            let symbol = self.board[index].0;
+            let neighbors = Direction::iter()
+                .map(|d| d.apply_offset(pos))
+                .filter(|&p| self.symbol(p).is_some_and(|s| s != Symbol::Empty))
+                .collect::<Vec<_>>();
+            if let Symbol::White(n) = symbol {
+                // every pass through a white node uses two of its lines
+                if neighbors.len() < n as usize * 2 {
+                    errors.push(ValidationError::WhiteCapacity {
+                        pos,
+                        required: n,
+                        neighbors: neighbors.len(),
+                    });
+                }
+            } else if let Some(color) = symbol.node_color() {
+                let reachable = neighbors
+                    .iter()
+                    .filter(|&&p| {
+                        let neighbor = self.board[self.index(p)].0;
+                        neighbor.node_color() == Some(color)
+                            || matches!(neighbor, Symbol::White(_))
+                                && self.allowed[self.index(p)].contains(color)
+                    })
+                    .count();
+                // a path ends on an endpoint and goes on from any other node
+                if reachable == 0 {
+                    errors.push(ValidationError::Unreachable { color, pos });
+                } else if reachable == 1 && !symbol.is_end() {
+                    errors.push(ValidationError::DeadEnd { color, pos });
+                }
+            }
+        }
+
+        ValidationReport { problems: errors }
+    }
+
+    // the first constraint on the lines of a node that no solution can
+    // meet, checked before the depth first search
+    //
+    // a path leaves its endpoints once, so a color has two nodes of odd
+    // degree, `EndpointCount` or `NoEndpoints` otherwise. Then every node
+    // needs as many neighbors it can connect to as its lines, the checks of
+    // `validate`, and the lines it misses among its free lines, those not
+    // drawn, crossing no line and going to a node with room left: a colored
+    // node lacking them is `MissingLines`, and a white node is `WhiteParity`
+    // unless they make up an even number of lines of each color, a line to
+    // another white node taking any color both allow
+    pub fn precheck(&self) -> Result<(), ValidationError> {
+        if let Some(problem) = self.validate().problems.into_iter().next() {
+            return Err(problem);
+        }
+        match (0..self.board.len()).find_map(|index| self.degree_problem(index)) {
+            Some(problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+
+    // the lines the node at `index` has in a solution
+    fn required_degree(&self, index: usize) -> u8 {
+        match self.board[index].0 {
+            Symbol::End(_) => 1,
+            Symbol::Node(_) => 2,
+            Symbol::White(n) => n * 2,
+            Symbol::Empty => 0,
+        }
+    }
+
+    // the line from `pos` in `direction`, with the node it goes to, if it
+    // is not drawn, crosses no line and that node has room left
+    fn free_line(&self, pos: Point, direction: Direction) -> Option<usize> {
+        let next = direction.apply_offset(pos);
+        if !self.in_bounds(next) {
+            return None;
+        }
+        let (store_pos, inner) = direction.store(pos);
+        if self.lines[self.index(store_pos)][inner as usize].is_some() {
+            return None;
+        }
+        if let Some((conflict, inner)) = direction.may_conflict(pos) {
+            if self.lines[self.index(conflict)][inner as usize].is_some() {
+                return None;
+            }
+        }
+        let index = self.index(next);
+        (self.degree[index] < self.required_degree(index)).then_some(index)
+    }
+
+    // the color of the line drawn from `pos` in `direction`, if any
+    fn line_color(&self, pos: Point, direction: Direction) -> Option<Color> {
+        if !self.in_bounds(direction.apply_offset(pos)) {
+            return None;
+        }
+        let (store_pos, inner) = direction.store(pos);
+        self.lines[self.index(store_pos)][inner as usize]
+    }
+
+    // whether the free lines of the node at `index` can make up the lines
+    // it misses
+    fn degree_problem(&self, index: usize) -> Option<ValidationError> {
+        let pos = self.pos(index);
+        let symbol = self.board[index].0;
+        let missing = self
+            .required_degree(index)
+            .saturating_sub(self.degree[index]);
+        let free = Direction::iter()
+            .filter_map(|d| self.free_line(pos, d))
+            .collect::<Vec<_>>();
+        if let Some(color) = symbol.node_color() {
+            let reachable = free
+                .iter()
+                .filter(|&&next| match self.board[next].0 {
+                    Symbol::White(_) => self.allowed[next].contains(color),
+                    neighbor => neighbor.node_color() == Some(color),
+                })
+                .count() as u8;
+            return (reachable < missing).then_some(ValidationError::MissingLines {
+                color,
+                pos,
+                missing,
+                free: reachable,
+            });
+        }
+        if !matches!(symbol, Symbol::White(_)) {
+            return None;
+        }
+        let allowed = self.allowed[index];
+        // lines to other white nodes, each taking any color both allow
+        let whites = free
+            .iter()
+            .filter(|&&next| {
+                matches!(self.board[next].0, Symbol::White(_))
+                    && Color::iter().any(|c| allowed.contains(c) && self.allowed[next].contains(c))
+            })
+            .count();
+        // per color, the most lines of the right parity its colored
+        // neighbors give, the colors needing a white line to fix their
+        // parity, those with a colored line left to pair with one and those
+        // with an odd number drawn, each needing at least one more line
+        let (mut most, mut needed, mut left, mut odd) = (0, 0, 0, 0);
+        for color in Color::iter().filter(|&c| allowed.contains(c)) {
+            let drawn = Direction::iter()
+                .filter(|&d| self.line_color(pos, d) == Some(color))
+                .count();
+            let colored = free
+                .iter()
+                .filter(|&&next| self.board[next].0.node_color() == Some(color))
+                .count();
+            odd += drawn % 2;
+            match (drawn % 2, colored) {
+                (1, 0) => needed += 1,
+                (odd, colored) => {
+                    most += colored - (colored - odd) % 2;
+                    left += (colored - odd) % 2;
+                }
+            }
+        }
+        let free = free.len() as u8;
+        let problem = ValidationError::WhiteParity { pos, missing, free };
+        if whites < needed || odd > missing as usize {
+            return Some(problem);
+        }
+        let paired = left.min(whites - needed);
+        most += needed + 2 * paired + (whites - needed - paired) / 2 * 2;
+        (most < missing as usize).then_some(problem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CancellationToken, Uniqueness};
+
+    fn problems(text: &str) -> Vec<ValidationError> {
+        Board::parse(text).unwrap().validate().problems().to_vec()
+    }
+
+    #[test]
+    fn a_solvable_board_is_valid() {
+        let report = Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap().validate();
+        assert!(report.is_valid());
+        assert_eq!(report.into_result(), Ok(()));
+    }
+
+    #[test]
+    fn endpoint_count() {
+        assert_eq!(
+            problems("RrR\nR.."),
+            [ValidationError::EndpointCount {
+                color: Color::Red,
+                count: 3,
+            }]
+        );
+    }
+
+    #[test]
+    fn no_endpoints() {
+        assert_eq!(
+            problems("rrr\nrrr"),
+            [ValidationError::NoEndpoints {
+                color: Color::Red,
+                pos: (0, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn white_capacity() {
+        assert_eq!(
+            problems("R3R\nr.r"),
+            [ValidationError::WhiteCapacity {
+                pos: (1, 0),
+                required: 3,
+                neighbors: 4,
+            }]
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        assert_eq!(
+            problems("RbR\nrrr\nB.B"),
+            [
+                ValidationError::Unreachable {
+                    color: Color::Blue,
+                    pos: (1, 0),
+                },
+                ValidationError::Unreachable {
+                    color: Color::Blue,
+                    pos: (0, 2),
+                },
+                ValidationError::Unreachable {
+                    color: Color::Blue,
+                    pos: (2, 2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dead_end() {
+        assert_eq!(
+            problems("RrRr"),
+            [ValidationError::DeadEnd {
+                color: Color::Red,
+                pos: (3, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let report = Board::parse("Rr.\n...\n..b").unwrap().validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.problems().len(), 4);
+        assert_eq!(report.to_string().lines().count(), 4);
+    }
+
+    #[test]
+    fn precheck_reports_the_first_violated_constraint() {
+        assert_eq!(
+            Board::parse("R2B\n2Gr\ngbR\n.GB").unwrap().precheck(),
+            Ok(())
+        );
+        let cases = [
+            (
+                "RrR\nR..",
+                ValidationError::EndpointCount {
+                    color: Color::Red,
+                    count: 3,
+                },
+            ),
+            (
+                "rrr\nrrr",
+                ValidationError::NoEndpoints {
+                    color: Color::Red,
+                    pos: (0, 0),
+                },
+            ),
+            (
+                "RrRr",
+                ValidationError::DeadEnd {
+                    color: Color::Red,
+                    pos: (3, 0),
+                },
+            ),
+            (
+                "R3R",
+                ValidationError::WhiteCapacity {
+                    pos: (1, 0),
+                    required: 3,
+                    neighbors: 2,
+                },
+            ),
+        ];
+        for (text, problem) in cases {
+            let mut board = Board::parse(text).unwrap();
+            assert_eq!(board.precheck(), Err(problem), "{}", text);
+            assert_eq!(board.solve(), None);
+            assert_eq!(board.stats().add_line_calls, 0);
+        }
+    }
+
+    #[test]
+    fn precheck_counts_the_colors_of_the_lines_of_a_white_node() {
+        // the white node has two neighbors, as `validate` asks, but a pass
+        // needs both its lines of one color
+        let mut board = Board::parse("RR1BB").unwrap();
+        assert!(board.validate().is_valid());
+        let problem = ValidationError::WhiteParity {
+            pos: (2, 0),
+            missing: 2,
+            free: 2,
+        };
+        assert_eq!(board.precheck(), Err(problem));
+        assert_eq!(board.solve(), None);
+        assert_eq!(board.stats().add_line_calls, 0);
+    }
+
+    #[test]
+    fn precheck_counts_only_the_free_lines() {
+        // the blue line crosses the only red line from the red endpoint
+        let mut board = Board::parse("RB.\nBrR").unwrap();
+        assert_eq!(board.precheck(), Ok(()));
+        board
+            .fix_lines(&[((1, 0), Direction::DownLeft, Color::Blue)])
+            .unwrap();
+        assert!(board.validate().is_valid());
+        assert_eq!(
+            board.precheck(),
+            Err(ValidationError::MissingLines {
+                color: Color::Red,
+                pos: (0, 0),
+                missing: 1,
+                free: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn no_search_starts_on_a_board_failing_precheck() {
+        let mut board = Board::parse("RR1BB").unwrap();
+        assert!(board.precheck().is_err());
+        assert_eq!(board.solve_observed(&mut ()), None);
+        assert_eq!(board.solve_all(None), []);
+        assert_eq!(board.uniqueness(), Uniqueness::NoSolution);
+        let token = CancellationToken::new();
+        assert_eq!(board.solve_cancellable(&token), Ok(None));
+        assert_eq!(board.stats().add_line_calls, 0);
+    }
+}
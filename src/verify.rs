@@ -0,0 +1,213 @@
+use crate::solver::{color_solved, white_solved};
+use crate::{Board, Color, Direction, IllegalMove, Point, Solution, StateError, Symbol};
+
+// Why the lines on a board are not a solution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    // a segment that cannot be drawn
+    IllegalSegment(IllegalMove),
+    // the board is inconsistent, see `Board::validate_state`
+    State(StateError),
+    // a node of `color` that no line reaches
+    Uncovered {
+        pos: Point,
+        color: Color,
+    },
+    // a white node passed another number of times than its number
+    WhitePasses {
+        pos: Point,
+        passes: u8,
+        expected: u8,
+    },
+    // a node with another number of lines than its paths need
+    NodeLines {
+        pos: Point,
+        lines: usize,
+        expected: usize,
+    },
+    // the lines of `color` do not form a single path between its endpoints
+    Disconnected(Color),
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::IllegalSegment(e) => write!(f, "{}", e),
+            VerifyError::State(e) => write!(f, "{}", e),
+            VerifyError::Uncovered { pos, color } => {
+                write!(f, "{} node {:?} is not covered", color, pos)
+            }
+            VerifyError::WhitePasses {
+                pos,
+                passes,
+                expected,
+            } => write!(
+                f,
+                "white node {:?} is passed {} times instead of {}",
+                pos, passes, expected
+            ),
+            VerifyError::NodeLines {
+                pos,
+                lines,
+                expected,
+            } => write!(
+                f,
+                "node {:?} has {} lines instead of {}",
+                pos, lines, expected
+            ),
+            VerifyError::Disconnected(color) => write!(
+                f,
+                "{} lines do not form one path between its endpoints",
+                color
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// Whether segments drawn by hand can still be completed, see
+// `Board::check_partial`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartialCheck {
+    // a solution keeping the segments
+    Extendable(Solution),
+    // the segment at this index leaves no solution: the segments before it
+    // can still be completed, and not with it
+    DeadEnd(usize),
+    // the puzzle has no solution, whatever is drawn
+    Unsolvable,
+    // a search timed out or was cancelled before telling
+    GaveUp,
+}
+
+impl Board {
+    // check that the current state is a complete and valid solution, telling
+    // the first node in reading order that is not satisfied
+    pub fn check_solved(&self) -> Result<(), VerifyError> {
+        self.validate_state().map_err(VerifyError::State)?;
+        if !white_solved(self) {
+            let (index, &(symbol, passes)) = self
+                .board
+                .iter()
+                .enumerate()
+                .find(|(_, &(symbol, passes))| matches!(symbol, Symbol::White(n) if n != passes))
+                .unwrap();
+            let Symbol::White(expected) = symbol else {
+                unreachable!()
+            };
+            return Err(VerifyError::WhitePasses {
+                pos: self.pos(index),
+                passes,
+                expected,
+            });
+        }
+        for color in Color::iter() {
+            if !color_solved(self, color) {
+                let index = (0..self.board.len())
+                    .find(|&i| self.board[i].0 == Symbol::color(color) && self.degree[i] == 0)
+                    .unwrap();
+                return Err(VerifyError::Uncovered {
+                    pos: self.pos(index),
+                    color,
+                });
+            }
+        }
+
+        // every pass through a white node uses two of its lines
+        let mut degree = vec![0; self.board.len()];
+        for &(pos, direction, _) in &self.result {
+            degree[self.index(pos)] += 1;
+            degree[self.index(direction.apply_offset(pos))] += 1;
+        }
+        for (index, (&(symbol, _), &lines)) in self.board.iter().zip(&degree).enumerate() {
+            let expected = match symbol {
+                Symbol::White(n) => 2 * usize::from(n),
+                Symbol::Empty => 0,
+                symbol if symbol.is_end() => 1,
+                _ => 2,
+            };
+            if lines != expected {
+                return Err(VerifyError::NodeLines {
+                    pos: self.pos(index),
+                    lines,
+                    expected,
+                });
+            }
+        }
+
+        match Color::iter().find(|&c| !self.color_connected(c)) {
+            Some(color) => Err(VerifyError::Disconnected(color)),
+            None => Ok(()),
+        }
+    }
+
+    // replay the segments of a proposed solution through `add_line` and
+    // check the result, leaving the segments on the board
+    pub fn verify(&mut self, segments: &[(Point, Direction, Color)]) -> Result<(), VerifyError> {
+        self.import_lines(segments)
+            .map_err(VerifyError::IllegalSegment)?;
+        self.check_solved()
+    }
+
+    // a segment missing from the legal but incomplete `segments`: the first
+    // one a solution keeping them adds, or None if no solution keeps them
+    //
+    // the segments of each color must start from one of its endpoints, as
+    // for `fix_lines`
+    pub fn missing_segment(
+        &self,
+        segments: &[(Point, Direction, Color)],
+    ) -> Option<(Point, Direction, Color)> {
+        let mut board = self.clone();
+        board.fix_lines(segments).ok()?;
+        board.solve()?.added().first().copied()
+    }
+
+    // tell whether the legal segments drawn by hand on the puzzle can still
+    // be completed into a solution, and otherwise which of them made it
+    // impossible, searching with the options of the board
+    //
+    // the segments of each color must start from one of its endpoints, as
+    // for `fix_lines`
+    pub fn check_partial(
+        &self,
+        segments: &[(Point, Direction, Color)],
+    ) -> Result<PartialCheck, IllegalMove> {
+        // a solution keeping the first `count` segments, and whether the
+        // search gave up without one
+        let solve = |count: usize| -> Result<(Option<Solution>, bool), IllegalMove> {
+            let mut board = self.clone();
+            board.fix_lines(&segments[..count])?;
+            let solution = board.solve();
+            Ok((solution, board.stats().timed_out || board.stats().cancelled))
+        };
+        match solve(segments.len())? {
+            (Some(solution), _) => return Ok(PartialCheck::Extendable(solution)),
+            (None, true) => return Ok(PartialCheck::GaveUp),
+            (None, false) if segments.is_empty() => return Ok(PartialCheck::Unsolvable),
+            (None, false) => {}
+        }
+
+        // fewer segments leave more solutions, so the shortest prefix that
+        // cannot be completed is found by bisection, `kept` segments being
+        // known to be completable, except for none, and `stuck` not
+        let (mut kept, mut stuck) = (0, segments.len());
+        while stuck - kept > 1 {
+            let middle = (kept + stuck) / 2;
+            match solve(middle)? {
+                (Some(_), _) => kept = middle,
+                (None, true) => return Ok(PartialCheck::GaveUp),
+                (None, false) => stuck = middle,
+            }
+        }
+        if kept == 0 {
+            match solve(0)? {
+                (Some(_), _) => {}
+                (None, true) => return Ok(PartialCheck::GaveUp),
+                (None, false) => return Ok(PartialCheck::Unsolvable),
+            }
+        }
+        Ok(PartialCheck::DeadEnd(stuck - 1))
+    }
+}
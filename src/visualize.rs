@@ -0,0 +1,85 @@
+use lyne::{Board, Color, Direction, Point, SearchObserver};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::time::{Duration, Instant};
+
+// How often `--visualize` redraws the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    // wait for Enter on the terminal after every move
+    Step,
+    // at most this many frames per second, skipping the moves in between
+    Fps(f64),
+}
+
+// Draws the board on stderr as the search places and takes back lines
+pub struct Visualizer {
+    pace: Pace,
+    last_frame: Option<Instant>,
+    // the terminal, to wait for a key while stdin holds the puzzle
+    tty: Option<BufReader<File>>,
+}
+
+impl Visualizer {
+    pub fn new(pace: Pace) -> Self {
+        let tty = match pace {
+            Pace::Step => File::open("/dev/tty").ok().map(BufReader::new),
+            Pace::Fps(_) => None,
+        };
+        Visualizer {
+            pace,
+            last_frame: None,
+            tty,
+        }
+    }
+
+    // draw the last state of the search with its verdict, which stays on
+    // the screen
+    pub fn finish(&mut self, board: &Board, verdict: &str) {
+        self.draw(board, None, verdict);
+    }
+
+    fn draw(&mut self, board: &Board, head: Option<Point>, status: &str) {
+        // move to the top left corner and clear the screen
+        let frame = format!("\x1b[H\x1b[2J{}{}\n", board.render_ansi(head), status);
+        let _ = io::stderr().write_all(frame.as_bytes());
+        self.last_frame = Some(Instant::now());
+    }
+
+    // `event` tells the move in step mode, where there is time to read it
+    fn on_move(&mut self, board: &Board, head: Point, event: impl FnOnce() -> String) {
+        let lines = || board.export_lines().len();
+        match self.pace {
+            Pace::Step => {
+                let status = format!("{}, {} lines, Enter for the next move", event(), lines());
+                self.draw(board, Some(head), &status);
+                if let Some(tty) = &mut self.tty {
+                    let _ = tty.read_line(&mut String::new());
+                }
+            }
+            Pace::Fps(fps) => {
+                let interval = Duration::from_secs_f64(1.0 / fps);
+                if self
+                    .last_frame
+                    .is_none_or(|last| last.elapsed() >= interval)
+                {
+                    self.draw(board, Some(head), &format!("{} lines", lines()));
+                }
+            }
+        }
+    }
+}
+
+impl SearchObserver for Visualizer {
+    fn on_place(&mut self, board: &Board, pos: Point, direction: Direction, color: Color) {
+        self.on_move(board, direction.apply_offset(pos), || {
+            format!("drew {} {} from {:?}", color, direction, pos)
+        });
+    }
+
+    fn on_remove(&mut self, board: &Board, pos: Point, direction: Direction, color: Color) {
+        self.on_move(board, pos, || {
+            format!("took back {} {} from {:?}", color, direction, pos)
+        });
+    }
+}
@@ -0,0 +1,30 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{solve, Json};
+
+// solve the puzzle `board_text` for a JavaScript caller, returning the JSON
+// document `{"solved": .., "segments": [{"color": .., "from": .., "to": ..}]}`,
+// or `{"error": ..}` for an invalid board
+pub fn solve_json(board_text: &str) -> String {
+    match solve(board_text) {
+        Ok(solution) => {
+            let mut entries = vec![("solved", solution.is_some().into())];
+            if let Some(solution) = solution {
+                entries.push(("segments", solution.to_json()));
+            }
+            Json::object(entries)
+        }
+        Err(e) => Json::object([("error", e.to_string().into())]),
+    }
+    .to_string()
+}
+
+// `solve(boardText)` for JavaScript, the document of `solve_json` as an
+// object
+//
+// named `solve_js` in Rust, where `solve` reads a puzzle into a `Solution`
+#[wasm_bindgen(js_name = solve)]
+pub fn solve_js(board_text: &str) -> JsValue {
+    // `solve_json` only writes valid JSON
+    js_sys::JSON::parse(&solve_json(board_text)).unwrap_or(JsValue::NULL)
+}
@@ -0,0 +1,58 @@
+import threading
+
+import pytest
+
+import lyne
+
+SOLVABLE = "R2B\n2Gr\ngbR\n.GB"
+
+
+def test_parse_reads_the_size_and_the_nodes():
+    board = lyne.Board.parse(SOLVABLE)
+    assert (board.width, board.height) == (3, 4)
+    assert board.symbols[0] == ["R", "2", "B"]
+    assert board.symbols[3] == [".", "G", "B"]
+
+
+def test_parse_rejects_bad_input():
+    with pytest.raises(ValueError, match="line 2"):
+        lyne.Board.parse("RrR\nR!R")
+    with pytest.raises(ValueError):
+        lyne.Board.parse("RrR\nRr")
+
+
+def test_solve_returns_each_path_between_its_endpoints():
+    paths = dict(lyne.Board.parse(SOLVABLE).solve())
+    assert set(paths) == {"Red", "Green", "Blue"}
+    assert paths["Red"] == [(0, 0), (1, 0), (2, 1), (2, 2)]
+    for path in paths.values():
+        for (x, y), (next_x, next_y) in zip(path, path[1:]):
+            assert max(abs(next_x - x), abs(next_y - y)) == 1
+
+
+def test_solve_returns_none_without_a_solution():
+    assert lyne.Board.parse("RbR\nBrB").solve() is None
+
+
+def test_validate_lists_every_problem():
+    board = lyne.Board.parse("R.r\n...\nB..")
+    with pytest.raises(ValueError) as error:
+        board.validate()
+    assert "Red" in str(error.value)
+    assert "Blue" in str(error.value)
+    with pytest.raises(ValueError):
+        board.solve()
+    lyne.Board.parse(SOLVABLE).validate()
+
+
+def test_solve_lets_other_threads_run():
+    results = []
+    threads = [
+        threading.Thread(target=lambda: results.append(lyne.Board.parse(SOLVABLE).solve()))
+        for _ in range(4)
+    ]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    assert len(results) == 4 and all(results)
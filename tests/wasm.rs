@@ -0,0 +1,26 @@
+// the function exported to JavaScript, run in a headless browser with
+// `wasm-pack test --headless --firefox --features wasm`
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn field(object: &JsValue, name: &str) -> JsValue {
+    js_sys::Reflect::get(object, &name.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn solves_a_3x3_board() {
+    let result = lyne::solve_js("RrR\n...\nBbB");
+    assert_eq!(field(&result, "solved").as_bool(), Some(true));
+    let segments = js_sys::Array::from(&field(&result, "segments"));
+    assert_eq!(segments.length(), 4);
+}
+
+#[wasm_bindgen_test]
+fn an_invalid_board_is_an_error() {
+    let result = lyne::solve_js("RrX");
+    assert!(field(&result, "error").as_string().is_some());
+}